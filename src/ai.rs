@@ -0,0 +1,202 @@
+use crate::prelude::*;
+
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub enum AiState {
+	Idle, //standing around, starts patrolling after a while
+	Patrol, //walking back and forth along whatever it's standing on
+	Chase, //going for the player
+	Jump, //in the air on the way up to the player
+	Flee, //running away at low hp
+}
+
+impl Default for AiState {
+	fn default() -> Self { AiState::Idle }
+}
+
+#[derive(Debug,Copy,Clone)]
+pub struct EnemyParams {
+	pub sight_range: f64, //notices the player within this distance
+	pub lose_range: f64, //gives up chasing past this distance
+	pub idle_ticks: f64, //how long it stands around before patrolling
+	pub patrol_speed: f64, //fraction of move_speed used while patrolling
+	pub edge_lookahead: f64, //how far past its front it checks for the end of the platform
+	pub jump_vel: f64,
+	pub jump_reach: f64, //only jumps up at the player when they're at most this far away sideways
+	pub flee_hp: f64, //fraction of max hp it runs away at
+	pub fly_speed: f64, //acceleration per tick for flyers
+	pub shoot_range: f64, //shooters fire at the player within this distance
+	pub shoot_interval: f64, //ticks between shots
+	pub keep_distance: f64, //shooters stop walking towards the player this close
+	pub projectile_speed: f64,
+	pub projectile_damage: f64,
+}
+
+impl Default for EnemyParams {
+	fn default() -> Self {
+		Self {
+			sight_range: 0.8,
+			lose_range: 1.2,
+			idle_ticks: 90.0,
+			patrol_speed: 0.5,
+			edge_lookahead: 0.02,
+			jump_vel: 0.08,
+			jump_reach: 0.4,
+			flee_hp: 0.25,
+			fly_speed: 0.0015,
+			shoot_range: 0.9,
+			shoot_interval: 90.0,
+			keep_distance: 0.5,
+			projectile_speed: 0.015,
+			projectile_damage: 5.0,
+		}
+	}
+}
+
+pub fn support<'a>(character: &dyn BoundingBox, platforms: &'a [Platform], broadphase: &Broadphase) -> Option<&'a Platform> { //whatever character is standing on
+	let feet = AABB { pos: character.pos() - vec2(0.0, 0.01), size: vec2(character.size().x, 0.01), vel: Vec2::zero() };
+	broadphase.platforms.query(feet.pos, feet.size).into_iter()
+		.filter(|&i| i < platforms.len())
+		.map(|i| &platforms[i])
+		.find(|p| feet.penetration(*p).is_some())
+}
+
+impl Enemy {
+	fn at_edge(&self, dir: f64, support: Option<&Platform>, params: &EnemyParams) -> bool { //walking dir would take us off the end of what we're standing on
+		support.map(|p| {
+			let ahead = self.centre_pos.x + dir * (self.size().x / 2.0 + params.edge_lookahead);
+			ahead < p.pos().x || ahead > p.pos().x + p.size().x
+		}).unwrap_or(false)
+	}
+	
+	fn set_ai(&mut self, ai: AiState, time: f64) {
+		if self.ai != ai {
+			self.ai = ai;
+			self.ai_since = time;
+		}
+	}
+	
+	fn jump(&mut self, params: &EnemyParams, time: f64) {
+		self.vel.y += params.jump_vel;
+		self.grounded = -1.0;
+		self.set_ai(AiState::Jump, time);
+	}
+	
+	fn follow_link(&mut self, link: NavLink, nav: &NavGraph, support: Option<&Platform>, params: &EnemyParams, time: f64) -> f64 { //heads for the next surface on the route
+		let n = nav.nodes[link.to];
+		let half = self.size().x / 2.0;
+		let grounded = time - self.grounded < 1.1;
+		if link.kind == LinkKind::Jump && !n.one_way && n.y > self.pos().y { //can't come up through it, so take off from past its edge and come down on top
+			let inwards = if self.centre_pos.x < (n.left + n.right) / 2.0 { 1.0 } else { -1.0 };
+			let take_off = if inwards > 0.0 { n.left - half - params.edge_lookahead } else { n.right + half + params.edge_lookahead };
+			let to_take_off = take_off - self.centre_pos.x;
+			if to_take_off.abs() < params.edge_lookahead || (self.at_edge(-inwards, support, params) && to_take_off * inwards < 0.0) { //there, or as close as we can walk
+				if grounded {
+					self.jump(params, time);
+				}
+				return inwards;
+			}
+			return to_take_off.signum();
+		}
+		let target_x = self.centre_pos.x.max(n.left + half).min(n.right - half); //nearest spot on it we could stand
+		let dir = if (target_x - self.centre_pos.x).abs() < EPSILON { 0.0 } else { (target_x - self.centre_pos.x).signum() };
+		if link.kind == LinkKind::Jump && grounded && (dir == 0.0 || self.at_edge(dir, support, params)) {
+			self.jump(params, time); //right under it or as close as we can walk
+		}
+		dir
+	}
+	
+	pub fn think(&mut self, player: &Player, params: &EnemyParams, nav: &NavGraph, platforms: &[Platform], broadphase: &Broadphase, time: f64) -> f64 { //picks what to do this tick, returns which way to walk and how hard
+		use AiState::*;
+		let to_player = player.centre_pos - self.centre_pos;
+		let dist = to_player.magnitude();
+		let grounded = time - self.grounded < 1.1;
+		let next = if self.hp < self.max_hp() * params.flee_hp {
+			Flee
+		} else {
+			match self.ai {
+				Jump if !grounded => Jump, //can't change our mind mid air
+				Idle | Patrol if dist < params.sight_range => Chase,
+				Chase | Jump if dist > params.lose_range => Idle,
+				Jump | Flee => Chase,
+				Idle if time - self.ai_since > params.idle_ticks => Patrol,
+				x => x,
+			}
+		};
+		self.set_ai(next, time);
+		if self.ai_dir == 0.0 {
+			self.ai_dir = 1.0;
+		}
+		let support = support(self, platforms, broadphase);
+		match self.ai {
+			Idle => 0.0,
+			Patrol => {
+				if self.at_edge(self.ai_dir, support, params) {
+					self.ai_dir = -self.ai_dir;
+				}
+				self.ai_dir * params.patrol_speed
+			},
+			Chase if self.kind == EnemyKind::Shooter && dist < params.keep_distance => 0.0, //close enough to shoot from
+			Chase => {
+				let feet = |x: &dyn BoundingBox| x.centre_pos() - vec2(0.0, x.size().y / 2.0);
+				let route = nav.surface_below(feet(self)).zip(nav.surface_below(feet(player)))
+					.and_then(|(a, b)| nav.path(a, b))
+					.and_then(|x| x.first().copied());
+				match route {
+					Some(link) => self.follow_link(link, nav, support, params, time),
+					None => { //same surface, or nowhere we know how to get to, so just go straight for them
+						if grounded && to_player.y > self.size().y / 2.0 && to_player.x.abs() < params.jump_reach {
+							self.jump(params, time); //player's up on something, go after them
+						}
+						to_player.x.signum()
+					},
+				}
+			},
+			Jump => to_player.x.signum(),
+			Flee => {
+				let dir = -to_player.x.signum();
+				if self.at_edge(dir, support, params) { 0.0 } else { dir } //cornered, jumping off would be worse
+			},
+		}
+	}
+	
+	pub fn fly(&mut self, player: &Player, params: &EnemyParams, time: f64) -> Vec2<f64> { //flyers don't need the nav graph, returns which way to accelerate
+		use AiState::*;
+		let to_player = player.centre_pos - self.centre_pos;
+		let dist = to_player.magnitude();
+		let next = if self.hp < self.max_hp() * params.flee_hp {
+			Flee
+		} else {
+			match self.ai {
+				Idle | Patrol if dist < params.sight_range => Chase,
+				Chase if dist > params.lose_range => Idle,
+				Jump | Flee => Chase,
+				x => x,
+			}
+		};
+		self.set_ai(next, time);
+		let dir = if dist < EPSILON { Vec2::zero() } else { to_player / dist };
+		match self.ai {
+			Chase => dir,
+			Flee => dir * -1.0,
+			_ => vec2(0.0, (time * 0.05).sin() * 0.3), //hover in place
+		}
+	}
+	
+	pub fn shoot(&mut self, player: &Player, params: &EnemyParams, time: f64) -> Option<Projectile> { //shooters fire straight at the player while chasing them
+		let to_player = player.centre_pos - self.centre_pos;
+		let dist = to_player.magnitude();
+		if self.kind != EnemyKind::Shooter || self.stitched() || self.ai != AiState::Chase || dist > params.shoot_range || dist < EPSILON || time - self.fired < params.shoot_interval {
+			return None;
+		}
+		self.fired = time;
+		Some(Projectile::new(self.centre_pos, to_player / dist * params.projectile_speed, params.projectile_damage, time))
+	}
+	
+	pub fn blocked(&mut self, params: &EnemyParams, time: f64) { //walked into a wall
+		match self.ai {
+			AiState::Patrol => self.ai_dir = -self.ai_dir,
+			AiState::Chase if time - self.grounded < 1.1 => self.jump(params, time),
+			_ => {},
+		}
+	}
+}
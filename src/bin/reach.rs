@@ -0,0 +1,52 @@
+//reports which platforms in a level the player can't get to, and whether the goal can be reached
+//cargo run --bin reach --no-default-features -- some.level [x y w h]
+//a goal given after the level replaces the one in it, if it has one
+use seamstress::prelude::*;
+use std::process::exit;
+
+fn main() {
+	let args = std::env::args().skip(1).collect::<Vec<_>>();
+	let goal = args.iter().skip(1).map(|x| x.parse::<f64>()).collect::<Result<Vec<_>, _>>();
+	let (path, goal) = match (args.first(), goal) {
+		(Some(path), Ok(goal)) if goal.is_empty() || goal.len() == 4 => (path, goal),
+		_ => {
+			eprintln!("usage: reach <level file> [goal x y w h]");
+			exit(2);
+		},
+	};
+	let src = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+		eprintln!("couldn't read {}: {}", path, e);
+		exit(2);
+	});
+	let mut game_state = parse_level(&src).unwrap_or_else(|e| {
+		eprintln!("{}: {}", path, e);
+		exit(2);
+	});
+	if let [x, y, w, h] = goal[..] {
+		game_state.goal = Some(Goal { centre_pos: vec2(x, y), size: vec2(w, h) });
+	}
+	let r = reachability(&game_state).unwrap_or_else(|e| {
+		eprintln!("{}: {}", path, e);
+		exit(2);
+	});
+	let mut ok = true;
+	for i in r.unreachable() { //numbered in the order they're in the file, polygons included
+		let p = &game_state.platforms[i];
+		println!("platform {} at {} {} can't be reached", i + 1, p.centre_pos.x, p.centre_pos.y);
+		ok = false;
+	}
+	for &i in r.unchecked.iter() {
+		println!("platform {} moves, not checked", i + 1);
+	}
+	match r.goal {
+		Some(true) => println!("goal can be reached"),
+		Some(false) => {
+			println!("goal can't be reached");
+			ok = false;
+		},
+		None => println!("level has no goal"),
+	}
+	if !ok {
+		exit(1);
+	}
+}
@@ -0,0 +1,81 @@
+//pairs up players for co-op and passes their messages between them, the game itself runs in the browsers
+//cargo run --bin relay --no-default-features --features relay -- [address]
+use std::io;
+use std::net::{TcpListener,TcpStream};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration,SystemTime,UNIX_EPOCH};
+use tungstenite::{accept,Error,Message,WebSocket};
+
+const DEFAULT_ADDR: &str = "127.0.0.1:9001";
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10); //anyone slower than this gets dropped rather than holding up a thread forever
+const POLL: Duration = Duration::from_millis(5); //how long a read waits on one player before checking the other
+
+fn handshake(stream: TcpStream) -> Result<WebSocket<TcpStream>, String> {
+	stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT)).map_err(|e| e.to_string())?;
+	let ws = accept(stream).map_err(|e| e.to_string())?;
+	ws.get_ref().set_read_timeout(Some(POLL)).map_err(|e| e.to_string())?;
+	Ok(ws)
+}
+
+fn relay(mut players: [WebSocket<TcpStream>; 2]) { //one thread owns both connections, so each only ever has one writer and pongs can't get mixed into forwarded messages
+	'relay: loop {
+		for i in 0..2 {
+			match players[i].read_message() {
+				Ok(Message::Text(x)) => if players[1 - i].write_message(Message::Text(x)).is_err() { break 'relay; },
+				Ok(Message::Close(_)) => break 'relay,
+				Ok(_) => {},
+				Err(Error::Io(e)) if e.kind() == io::ErrorKind::WouldBlock || e.kind() == io::ErrorKind::TimedOut => { //nothing from them yet
+					if let Err(Error::Io(e)) = players[i].write_pending() { //sends any pong that's queued up
+						if e.kind() != io::ErrorKind::WouldBlock {
+							break 'relay;
+						}
+					}
+				},
+				Err(_) => break 'relay,
+			}
+		}
+	}
+	for ws in players.iter_mut() { //take the other player down too, the game can't carry on without both
+		let _ = ws.close(None);
+		let _ = ws.write_pending();
+	}
+}
+
+fn main() {
+	let addr = std::env::args().nth(1).unwrap_or_else(|| DEFAULT_ADDR.to_string());
+	let listener = TcpListener::bind(&addr).unwrap_or_else(|e| panic!("couldn't listen on {}: {}", addr, e));
+	println!("relay listening on ws://{}", addr);
+	let (connected, arrivals) = mpsc::channel();
+	thread::spawn(move || for stream in listener.incoming() { //handshakes happen off this thread so one slow client can't stop anyone else connecting
+		let connected = connected.clone();
+		match stream {
+			Ok(x) => {
+				thread::spawn(move || match handshake(x) {
+					Ok(ws) => { let _ = connected.send(ws); },
+					Err(e) => println!("connection failed: {}", e),
+				});
+			},
+			Err(e) => println!("connection failed: {}", e),
+		}
+	});
+	let mut waiting: Option<WebSocket<TcpStream>> = None;
+	for mut ws in arrivals {
+		let mut first = match waiting.take() {
+			Some(x) => x,
+			None => { waiting = Some(ws); continue; },
+		};
+		let seed = SystemTime::now().duration_since(UNIX_EPOCH).map(|x| x.as_nanos() as u64).unwrap_or(0);
+		if first.write_message(Message::Text(format!("hello 0 {}", seed))).is_err() {
+			waiting = Some(ws); //first one left while waiting, this one waits instead
+			continue;
+		}
+		if let Err(e) = ws.write_message(Message::Text(format!("hello 1 {}", seed))) {
+			println!("couldn't pair: {}", e);
+			let _ = first.close(None);
+			continue;
+		}
+		thread::spawn(move || relay([first, ws]));
+		println!("paired two players, seed {}", seed);
+	}
+}
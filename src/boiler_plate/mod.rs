@@ -20,6 +20,10 @@ pub fn window() -> Window {
 	web_sys::window().expect("no global `window` exists")
 }
 
+pub fn now() -> f64 {
+	window().performance().expect(l!()).now()
+}
+
 pub fn document() -> Document {
 	window().document().expect(l!())
 }
@@ -46,7 +50,9 @@ pub fn setup_input_events() {
 		false
 	});
 	bind!(canvas(), "click", move|_: MouseEvent| {
-		input_events().push(InputEvent::LeftClick);
+		if let Some(c) = mouse_screen_coords() { //screen space is world space offset by the camera, use what was last drawn since that's what got clicked on
+			input_events().push(InputEvent::LeftClick(c + uniforms().cam_pos));
+		}
 	});
 	bind!(canvas(), "contextmenu", move|_: MouseEvent| {
 		input_events().push(InputEvent::RightClick);
@@ -62,33 +68,64 @@ pub fn setup_input_events() {
 	});
 }
 
-#[derive(Debug,Clone)]
-pub enum InputEvent {
-	Wheel(f64),
-	LeftClick,
-	RightClick,
-	KeyDown(String),
-	KeyUp(String),
-}
-
 use std::sync::atomic::{Ordering::Relaxed,AtomicU64};
 pub fn initial_setup() {
 	console_error_panic_hook::set_once();
 	unsafe {
 		macro init($($name: ident,)*) { $($name = Some(RefCell::default());)* }
-		init!(INPUT_EVENTS,UNIFORMS,OLD_UNIFORMS,);
+		init!(INPUT_EVENTS,NET_MESSAGES,UNIFORMS,OLD_UNIFORMS,);
 	}
 }
 
 static mut INPUT_EVENTS: Option<RefCell<Vec<InputEvent>>> = None;
 pub fn input_events() -> RefMut<'static, Vec<InputEvent>> { unsafe { INPUT_EVENTS.as_ref().unwrap().borrow_mut() } }
 
+static mut NET_MESSAGES: Option<RefCell<Vec<String>>> = None;
+pub fn net_messages() -> RefMut<'static, Vec<String>> { unsafe { NET_MESSAGES.as_ref().unwrap().borrow_mut() } }
+
 static mut UNIFORMS: Option<RefCell<UniformData>> = None;
 pub fn uniforms() -> UniformData { unsafe { UNIFORMS.as_ref().unwrap().borrow().clone() } }
 pub fn set_uniforms(x: UniformData) { unsafe { *UNIFORMS.as_ref().unwrap().borrow_mut() = x; } }
 static mut OLD_UNIFORMS: Option<RefCell<UniformData>> = None;
 pub fn old_uniforms() -> RefMut<'static, UniformData> { unsafe { OLD_UNIFORMS.as_ref().unwrap().borrow_mut() } }
-pub fn time() -> f64 { uniforms().time }
+
+#[derive(Debug,Default,Copy,Clone)]
+pub struct CryptoRng; //browser randomness, for anything that doesn't need to be reproducible
+
+impl Rng for CryptoRng {
+	fn next(&mut self) -> u64 {
+		let mut x = [0; 8];
+		window().crypto().expect(l!()).get_random_values_with_u8_array(&mut x).expect(l!());
+		//log!("random values: {:?}",x);
+		u64::from_ne_bytes(x)
+	}
+}
+
+pub fn save_to_slot(slot: usize, game_state: &GameState) -> Result<(), String> { //localStorage can be turned off or full, neither should take the game down
+	let storage = window().local_storage().ok().flatten().ok_or("no localStorage")?;
+	storage.set_item(&format!("seamstress_save_{}", slot), &to_hex(&save_game(game_state))).map_err(|e| format!("{:?}", e))
+}
+
+pub fn load_from_slot(slot: usize) -> Result<GameState, String> {
+	let storage = window().local_storage().ok().flatten().ok_or("no localStorage")?;
+	let save = storage.get_item(&format!("seamstress_save_{}", slot)).ok().flatten().ok_or(format!("slot {} is empty", slot))?;
+	load_game(&from_hex(&save)?)
+}
+
+pub fn relay_url() -> Option<String> { //co-op is on when the page is opened with ?relay=ws://host:port
+	let search = window().location().search().ok()?;
+	search.trim_start_matches('?').split('&').find_map(|x| x.strip_prefix("relay=")).map(|x| x.to_string())
+}
+
+pub fn connect_relay(url: &str) -> Result<WebSocket, JsValue> { //messages from the relay pile up in net_messages until the game loop gets to them
+	let ws = WebSocket::new(url)?;
+	bind!(ws, "message", move|e: MessageEvent| {
+		if let Some(s) = e.data().as_string() {
+			net_messages().push(s);
+		}
+	});
+	Ok(ws)
+}
 
 /*static ID: AtomicU64 = AtomicU64::new(0);
 pub fn new_id() -> u64 { ID.fetch_add(1, Relaxed) }*/
@@ -105,7 +142,7 @@ pub fn set_mouse_coords(c: Vec2<i32>) {
 		SCREEN_COORDS.0.store(f64::NAN.to_bits(), Relaxed); SCREEN_COORDS.1.store(f64::NAN.to_bits(), Relaxed);
 	}
 }
-/*pub fn mouse_screen_coords() -> Option<Vec2<f64>> {
+pub fn mouse_screen_coords() -> Option<Vec2<f64>> {
 	let c = vec2(f64::from_bits(SCREEN_COORDS.0.load(Relaxed)), f64::from_bits(SCREEN_COORDS.1.load(Relaxed)));
-	if c.is_nan().or() { None } else { Some(c) }
-}*/
+	if c.x.is_nan() || c.y.is_nan() { None } else { Some(c) }
+}
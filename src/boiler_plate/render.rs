@@ -115,7 +115,7 @@ pub struct UniformData {
 }
 
 impl UniformData {
-	pub fn add_time(mut self, x: f64) -> Self { self.time += x; set_uniforms(self); self }
+	pub fn set_time(mut self, x: f64) -> Self { self.time = x; set_uniforms(self); self }
 	pub fn set_cam_pos(mut self, x: Vec2<f64>) -> Self { self.cam_pos = x; set_uniforms(self); self }
 }
 
@@ -0,0 +1,94 @@
+use crate::prelude::*;
+
+const CELL_SIZE: f64 = 0.25;
+
+#[derive(Debug,Clone)]
+pub struct SpatialHash { //uniform grid of indices into some list, so we only test things that are nearby
+	pub cell_size: f64,
+	cells: HashMap<(i64, i64), Vec<usize>>,
+}
+
+impl Default for SpatialHash {
+	fn default() -> Self { Self::new(CELL_SIZE) }
+}
+
+impl SpatialHash {
+	pub fn new(cell_size: f64) -> Self {
+		Self {
+			cell_size,
+			cells: HashMap::new(),
+		}
+	}
+	
+	pub fn clear(&mut self) {
+		self.cells.values_mut().for_each(|x| x.clear()); //keep the allocations around since we rebuild every tick
+	}
+	
+	fn cell_range(&self, pos: Vec2<f64>, size: Vec2<f64>) -> ((i64, i64), (i64, i64)) {
+		let cell = |x: f64| (x / self.cell_size).floor() as i64;
+		((cell(pos.x), cell(pos.y)), (cell(pos.x + size.x), cell(pos.y + size.y)))
+	}
+	
+	pub fn insert(&mut self, i: usize, pos: Vec2<f64>, size: Vec2<f64>) {
+		let ((x0, y0), (x1, y1)) = self.cell_range(pos, size);
+		for x in x0..=x1 {
+			for y in y0..=y1 {
+				self.cells.entry((x, y)).or_insert_with(Vec::new).push(i);
+			}
+		}
+	}
+	
+	pub fn query(&self, pos: Vec2<f64>, size: Vec2<f64>) -> Vec<usize> { //sorted and deduplicated so results come out in the same order as the list
+		let ((x0, y0), (x1, y1)) = self.cell_range(pos, size);
+		let mut r = Vec::new();
+		for x in x0..=x1 {
+			for y in y0..=y1 {
+				if let Some(cell) = self.cells.get(&(x, y)) {
+					r.extend_from_slice(cell);
+				}
+			}
+		}
+		r.sort();
+		r.dedup();
+		r
+	}
+}
+
+#[derive(Debug,Default,Clone)]
+pub struct Broadphase {
+	pub platforms: SpatialHash,
+	pub enemies: SpatialHash,
+}
+
+impl Broadphase {
+	pub fn rebuild(&mut self, platforms: &[Platform], enemies: &[Enemy]) {
+		self.platforms.clear();
+		for (i, p) in platforms.iter().enumerate() {
+			let (pos, size) = swept_bounds(p.pos(), p.size(), p.vel());
+			self.platforms.insert(i, pos, size);
+		}
+		self.rebuild_enemies(enemies);
+	}
+	
+	pub fn rebuild_enemies(&mut self, enemies: &[Enemy]) {
+		self.enemies.clear();
+		for (i, e) in enemies.iter().enumerate() {
+			let (pos, size) = swept_bounds(e.pos(), e.size(), e.vel());
+			self.enemies.insert(i, pos, size);
+		}
+	}
+	
+	pub fn nearby<'a>(&self, platforms: &'a [Platform], enemies: &'a [Enemy], mover: &dyn BoundingBox, movement: Vec2<f64>, skip_enemy: Option<usize>) -> Vec<(bool, &'a dyn BoundingBox)> { //everything mover could touch while moving by movement, enemies first
+		let (pos, size) = swept_bounds(mover.pos(), mover.size(), movement);
+		let mut r = Vec::new();
+		r.extend(self.enemies.query(pos, size).into_iter().filter(|&i| Some(i) != skip_enemy && i < enemies.len()).map(|i| (true, &enemies[i] as &dyn BoundingBox)));
+		r.extend(self.platforms.query(pos, size).into_iter().filter(|&i| i < platforms.len()).map(|i| (false, &platforms[i] as &dyn BoundingBox)));
+		r
+	}
+}
+
+pub fn swept_bounds(pos: Vec2<f64>, size: Vec2<f64>, movement: Vec2<f64>) -> (Vec2<f64>, Vec2<f64>) { //box covering pos/size both before and after moving
+	let a = vec2(pos.x.min(pos.x + movement.x), pos.y.min(pos.y + movement.y));
+	let b = vec2((pos.x + size.x).max(pos.x + size.x + movement.x), (pos.y + size.y).max(pos.y + size.y + movement.y));
+	(a, b - a)
+}
@@ -0,0 +1,119 @@
+use crate::prelude::*;
+use sha3::{Digest, Sha3_256};
+use std::collections::BTreeMap;
+
+//the state hash covers exactly what a save does, so anything that matters to the sim gets hashed without a second list to keep up to date
+pub const HASH_INTERVAL: u64 = 30; //ticks between hashes, hashing every tick would mostly be wasted
+
+pub type StateHash = [u8; 32];
+
+pub fn state_hash(game_state: &GameState) -> StateHash {
+	let mut w = Writer::default();
+	game_state.save(&mut w);
+	let mut r = [0; 32];
+	r.copy_from_slice(&Sha3_256::digest(&w.buf));
+	r
+}
+
+pub fn parse_hash(s: &str) -> Result<StateHash, String> { //hashes are written as hex, same as saves
+	let x = from_hex(s)?;
+	let mut r = [0; 32];
+	if x.len() != r.len() {
+		return Err(format!("hash should be {} bytes, found {}", r.len(), x.len()));
+	}
+	r.copy_from_slice(&x);
+	Ok(r)
+}
+
+#[derive(Debug,Clone,Default,PartialEq)]
+pub struct HashStream { //state hashes from one run, keyed by the tick they were taken after
+	pub hashes: BTreeMap<u64, StateHash>,
+}
+
+impl HashStream {
+	pub fn record(&mut self, tick: u64, game_state: &GameState) {
+		if tick % HASH_INTERVAL == 0 {
+			self.hashes.insert(tick, state_hash(game_state));
+		}
+	}
+	
+	pub fn first_divergence(&self, other: &HashStream) -> Option<u64> { //earliest tick both runs hashed where they don't agree
+		self.hashes.iter().find(|(t, h)| other.hashes.get(t).map(|x| x != *h).unwrap_or(false)).map(|x| *x.0)
+	}
+	
+	pub fn last_agreement(&self, other: &HashStream) -> Option<u64> { //latest tick both runs hashed where they agree, before any divergence
+		let end = self.first_divergence(other).unwrap_or(u64::MAX);
+		self.hashes.iter().rev().find(|(t, h)| **t < end && other.hashes.get(t) == Some(*h)).map(|x| *x.0)
+	}
+}
+
+pub trait Dump { //flattens something into (field path, value) pairs, for finding exactly where two states differ
+	fn dump(&self, path: &str, out: &mut Vec<(String, String)>);
+}
+
+macro dump_debug($($t: ty,)*) {
+	$(impl Dump for $t {
+		fn dump(&self, path: &str, out: &mut Vec<(String, String)>) { out.push((path.to_string(), format!("{:?}", self))); }
+	})*
+}
+dump_debug!(u8,i8,u32,u64,f64,usize,bool,Collision,PathMode,Easing,AiState,EnemyKind,);
+
+impl<T: Dump> Dump for Vec2<T> {
+	fn dump(&self, path: &str, out: &mut Vec<(String, String)>) {
+		self.x.dump(&format!("{}.x", path), out);
+		self.y.dump(&format!("{}.y", path), out);
+	}
+}
+
+impl<T: Dump> Dump for Option<T> {
+	fn dump(&self, path: &str, out: &mut Vec<(String, String)>) {
+		match self {
+			Some(x) => x.dump(path, out),
+			None => out.push((path.to_string(), "None".to_string())),
+		}
+	}
+}
+
+impl<T: Dump> Dump for [T] {
+	fn dump(&self, path: &str, out: &mut Vec<(String, String)>) {
+		out.push((format!("{}.len", path), self.len().to_string())); //so a missing element shows up as the length rather than whatever came after it
+		self.iter().enumerate().for_each(|(i, x)| x.dump(&format!("{}[{}]", path, i), out));
+	}
+}
+
+impl<T: Dump> Dump for Vec<T> {
+	fn dump(&self, path: &str, out: &mut Vec<(String, String)>) { self[..].dump(path, out) }
+}
+
+impl<T: Dump, const N: usize> Dump for [T; N] {
+	fn dump(&self, path: &str, out: &mut Vec<(String, String)>) { self[..].dump(path, out) }
+}
+
+impl Dump for RandGen {
+	fn dump(&self, path: &str, out: &mut Vec<(String, String)>) { self.state().dump(&format!("{}.state", path), out) }
+}
+
+pub fn dump_state(game_state: &GameState) -> Vec<(String, String)> {
+	let mut r = Vec::new();
+	game_state.dump("game_state", &mut r);
+	r
+}
+
+pub fn first_difference(a: &GameState, b: &GameState) -> Option<String> { //eg "game_state.enemies[2].vel.x: 0.013 vs 0.0131"
+	let (a, b) = (dump_state(a), dump_state(b));
+	for i in 0..a.len().max(b.len()) {
+		match (a.get(i), b.get(i)) {
+			(Some(x), Some(y)) if x == y => {},
+			(Some(x), Some(y)) if x.0 == y.0 => return Some(format!("{}: {} vs {}", x.0, x.1, y.1)),
+			(Some(x), Some(y)) => return Some(format!("{} = {} vs {} = {}", x.0, x.1, y.0, y.1)),
+			(Some(x), None) => return Some(format!("{}: {} vs nothing", x.0, x.1)),
+			(None, Some(y)) => return Some(format!("{}: nothing vs {}", y.0, y.1)),
+			(None, None) => {},
+		}
+	}
+	None
+}
+
+pub fn diff_saves(a: &[u8], b: &[u8]) -> Result<Option<String>, String> { //for states from two different machines, eg dumped when co-op desyncs
+	Ok(first_difference(&load_game(a)?, &load_game(b)?))
+}
@@ -1,75 +1,340 @@
 use crate::prelude::*;
-use crate::boiler_plate::InputEvent;
 
-const GRAVITY: f64 = 0.0035;
-const DRAG: Vec2<f64> = Vec2{ x: 15.0, y: 2.0, };
+pub const GRAVITY: f64 = 0.0035;
+pub const DRAG: Vec2<f64> = Vec2{ x: 15.0, y: 2.0, };
 const FRICTION: f64 = 0.5;
+pub const EPSILON: f64 = 1e-9;
+const DEPENETRATION_ITERATIONS: usize = 4;
+const RIDING_TOLERANCE: f64 = 1e-6;
+const WALKABLE_SLOPE: f64 = 0.6; //y component of a surface normal that's flat enough to stand on rather than slide off
+const ROPE_ITERATIONS: usize = 8; //passes over the thread's length constraints per tick, more is stiffer
+const BLAST_TICKS: f64 = 20.0; //how long an explosion stays on screen
+const STITCH_TICKS: f64 = 180.0; //how long enemies caught in a loop of thread stay stunned
+const ONE_WAY_CATCH: f64 = 0.05; //how deep into a one way slope something can be and still get put on top, any deeper and they're passing through it
+const BRIDGE_TICKS: f64 = 600.0;
+const PROJECTILE_TICKS: f64 = 240.0; //how long a projectile flies before it fizzles out
+const BRIDGE_DURABILITY: f64 = 240.0; //ticks of one enemy standing on it, two enemies wear it twice as fast
+const BRIDGE_MAX_ENEMIES: usize = 2; //any more than this standing on it at once and it snaps
+const BRIDGE_MAX_LENGTH: f64 = 1.5;
+const BRIDGE_THICKNESS: f64 = 0.02;
+const NEEDLE_SPEED: f64 = 0.06;
+const NEEDLE_RANGE: f64 = 1.5;
+const NEEDLE_SUBSTEPS: usize = 4; //so the needle can't fly through thin platforms
+const REEL_SPEED: f64 = 0.01;
+const MIN_GRAPPLE_LENGTH: f64 = 0.1;
 
-#[derive(Debug)]
+#[derive(Debug,Clone)]
+pub enum InputEvent {
+	Wheel(f64),
+	LeftClick(Vec2<f64>), //where in the world the mouse was
+	RightClick,
+	KeyDown(String),
+	KeyUp(String),
+}
+
+#[derive(Debug,Clone)]
 pub struct GameState {
 	pub player: Player,
+	pub coop: Option<Player>, //second player, when playing co-op
+	pub player_start: Vec2<f64>,
 	pub platforms: Vec<Platform>,
 	pub enemies: Vec<Enemy>,
+	pub projectiles: Vec<Projectile>,
+	pub spawners: Vec<Spawner>,
+	pub waves: WaveDirector,
+	pub hazards: Vec<Hazard>,
+	pub goal: Option<Goal>,
+	pub time: f64, //the simulation clock, only advanced by tick
+	pub rng: RandGen, //all the randomness in the sim comes from here so runs can be replayed
+	pub cam_pos: Vec2<f64>,
+	pub prev_cam_pos: Vec2<f64>,
+	pub follow: usize, //which player the camera follows, 1 for the coop player
+	pub enemy_params: EnemyParams,
+	pub nav: [NavGraph; 3], //one per EnemyKind since they're different sizes, built from the level's platforms by build_nav, not saved since it can always be rebuilt
+	pub show_nav: bool,
+	pub broadphase: Broadphase,
+}
+
+impl Default for GameState {
+	fn default() -> Self { Self::new() }
 }
 
 impl GameState {
 	pub fn new() -> Self {
 		Self {
 			player: Player::new(),
+			coop: None,
+			player_start: Vec2::zero(),
 			platforms: Vec::new(),
 			enemies: Vec::new(),
+			projectiles: Vec::new(),
+			spawners: Vec::new(),
+			waves: WaveDirector::default(),
+			hazards: Vec::new(),
+			goal: None,
+			time: 0.0,
+			rng: RandGen::new(0),
+			cam_pos: Vec2::zero(),
+			prev_cam_pos: Vec2::zero(),
+			follow: 0,
+			enemy_params: EnemyParams::default(),
+			nav: Default::default(),
+			show_nav: false,
+			broadphase: Broadphase::default(),
+		}
+	}
+	
+	pub fn seed(&mut self, seed: u64) { //the wave director gets its own stream so spawns don't depend on anything else
+		self.rng = RandGen::new(seed);
+		self.waves.reseed(seed);
+	}
+	
+	pub fn reset_player(&mut self) {
+		self.player = Player::spawn(self.player_start);
+	}
+	
+	pub fn build_nav(&mut self) { //needs doing whenever the level's platforms change
+		for &kind in [EnemyKind::Walker, EnemyKind::Shooter].iter() { //flyers don't use one, theirs stays empty
+			self.nav[kind as usize] = NavGraph::new(&self.platforms, &Envelope::enemy(&self.enemy_params, kind));
 		}
 	}
 	
+	pub fn nav_for(&self, kind: EnemyKind) -> &NavGraph {
+		&self.nav[kind as usize]
+	}
+	
+	pub fn add_coop_player(&mut self) {
+		self.coop = Some(Player::spawn(self.player_start));
+	}
+	
+	pub fn players(&self) -> impl Iterator<Item = &Player> {
+		std::iter::once(&self.player).chain(self.coop.as_ref())
+	}
+	
 	pub fn tick(&mut self, delta: f64) {
-		self.player.do_movement(&self.platforms, &mut self.enemies, delta);
-		if self.player.hp < 0.0 {
-			self.player = Player::new();
+		self.time += delta;
+		let time = self.time;
+		for (i, s) in self.spawners.iter_mut().enumerate() {
+			let alive = self.enemies.iter().filter(|x| x.spawner == Some(i)).count();
+			if let Some(e) = s.spawn(time, alive, &mut self.waves) {
+				self.enemies.push(Enemy { spawner: Some(i), .. e });
+			}
 		}
-		let player = &mut self.player;
-		let platforms = &self.platforms;
-		let enemies = &mut self.enemies;
-		for i in 0..enemies.len() {
-			let mut e = enemies.remove(i);
-			e.do_movement(player, platforms, enemies);
-			enemies.insert(i, e);
+		self.player.prev_pos = self.player.centre_pos;
+		if let Some(p) = &mut self.coop { p.prev_pos = p.centre_pos; }
+		self.enemies.iter_mut().for_each(|x| x.prev_pos = x.centre_pos);
+		self.prev_cam_pos = self.cam_pos;
+		self.move_platforms(time);
+		self.broadphase.rebuild(&self.platforms, &self.enemies);
+		for p in std::iter::once(&mut self.player).chain(self.coop.as_mut()) {
+			p.do_movement(&self.platforms, &mut self.enemies, &mut self.projectiles, &self.broadphase, delta, time, &mut self.rng);
+			for h in self.hazards.iter() {
+				if p.penetration(h).is_some() {
+					p.hp -= h.damage * delta;
+				}
+			}
+			if p.hp < 0.0 {
+				*p = Player::spawn(self.player_start);
+			}
+			self.broadphase.rebuild_enemies(&self.enemies); //player might have killed some
 		}
+		for i in 0..self.enemies.len() {
+			let mut e = std::mem::take(&mut self.enemies[i]); //leaves a placeholder at i, which do_movement skips
+			let target = match &self.coop { //go for whoever's closest
+				Some(c) if (c.centre_pos - e.centre_pos).magnitude() < (self.player.centre_pos - e.centre_pos).magnitude() => c,
+				_ => &self.player,
+			};
+			e.do_movement(i, target, &self.enemy_params, self.nav_for(e.kind), &self.platforms, &self.enemies, &self.broadphase, time);
+			if let Some(p) = e.shoot(target, &self.enemy_params, time) {
+				self.projectiles.push(p);
+			}
+			self.enemies[i] = e;
+		}
+		self.move_projectiles(time);
+		self.wear_bridges(time, delta);
+		let followed = match &self.coop {
+			Some(c) if self.follow == 1 => c.centre_pos,
+			_ => self.player.centre_pos,
+		};
+		let n = 10.0;
+		self.cam_pos = (self.cam_pos * n + followed) / (n+1.0);
 	}
 	
-	pub fn input_event(&mut self, e: InputEvent) {
-		use InputEvent::*;
-		match e {
-			KeyDown(k) => match k.as_str() {
-				"d" | "ArrowRight" => self.player.movement[0] = true,
-				"a" | "ArrowLeft" => self.player.movement[1] = true,
-				"w" | "ArrowUp" => self.player.movement[2] = true,
-				"x" => self.player.detonate_thread(),
-				_ => {},
+	fn move_projectiles(&mut self, time: f64) { //projectiles go until they hit a platform or a player, or run out of time
+		for i in (0..self.projectiles.len()).rev() {
+			let p = &mut self.projectiles[i];
+			let mut gone = p.update(&self.platforms, &self.broadphase) || time >= p.expires;
+			for player in std::iter::once(&mut self.player).chain(self.coop.as_mut()) {
+				if !gone && player.penetration(&*p).is_some() {
+					player.hp -= p.damage;
+					gone = true;
+				}
 			}
-			KeyUp(k) => match k.as_str() {
-				"d" | "ArrowRight" => self.player.movement[0] = false,
-				"a" | "ArrowLeft" => self.player.movement[1] = false,
-				"w" | "ArrowUp" => self.player.movement[2] = false,
-				_ => {},
+			if gone {
+				self.projectiles.remove(i);
+			}
+		}
+	}
+	
+	fn move_platforms(&mut self, time: f64) { //moves platforms along their paths, carrying anyone standing on them and shoving anyone in the way
+		if self.platforms.iter().all(|x| x.path.is_none()) {
+			return;
+		}
+		self.broadphase.rebuild(&self.platforms, &self.enemies); //things that move get inserted again where they end up, so later queries still find them
+		let r = vec2(RIDING_TOLERANCE, RIDING_TOLERANCE);
+		let mut crushed_enemies = vec![false; self.enemies.len()];
+		for i in 0..self.platforms.len() {
+			if self.platforms[i].path.is_none() {
+				continue;
+			}
+			let (old_pos, size) = (self.platforms[i].pos(), self.platforms[i].size());
+			let players_riding = self.players().map(|x| is_riding(x, &self.platforms[i])).collect::<Vec<_>>();
+			let enemies_riding = self.broadphase.enemies.query(old_pos - r, size + r * 2.0).into_iter()
+				.filter(|&j| j < self.enemies.len() && is_riding(&self.enemies[j], &self.platforms[i]))
+				.collect::<Vec<_>>();
+			self.platforms[i].update(time);
+			let (pos, size) = swept_bounds(old_pos, size, self.platforms[i].vel);
+			self.broadphase.platforms.insert(i, pos, size);
+			let swept = AABB { pos, size, vel: Vec2::zero() };
+			for (p, &riding) in std::iter::once(&mut self.player).chain(self.coop.as_mut()).zip(players_riding.iter()) {
+				if !riding && swept.penetration(p).is_none() {
+					continue; //nowhere near it
+				}
+				let (correction, crushed) = carry(p, riding, &self.platforms, i, &self.broadphase);
+				p.centre_pos += correction;
+				if crushed {
+					p.hp = -1.0;
+				}
+			}
+			for j in self.broadphase.enemies.query(pos - r, size + r * 2.0) {
+				let riding = enemies_riding.contains(&j);
+				if j >= self.enemies.len() || crushed_enemies[j] || (!riding && swept.penetration(&self.enemies[j]).is_none()) {
+					continue;
+				}
+				let (correction, crushed) = carry(&self.enemies[j], riding, &self.platforms, i, &self.broadphase);
+				self.enemies[j].centre_pos += correction;
+				self.broadphase.enemies.insert(j, self.enemies[j].pos(), self.enemies[j].size());
+				crushed_enemies[j] = crushed;
 			}
+		}
+		for j in (0..self.enemies.len()).rev().filter(|&j| crushed_enemies[j]) { //removed at the end so indices stay put for the broadphase
+			self.enemies.remove(j);
+		}
+	}
+	
+	pub fn input_event(&mut self, e: InputEvent) {
+		self.input_event_for(0, e);
+	}
+	
+	pub fn input_event_for(&mut self, player: usize, e: InputEvent) { //player 0 is self.player, 1 is the coop player
+		let time = self.time;
+		match &e {
+			InputEvent::RightClick => self.pull_bridge(player),
+			InputEvent::KeyDown(k) if k == "e" => self.pull_bridge(player),
+			_ => {},
+		}
+		match (player, &mut self.coop) {
+			(0, _) => self.player.input_event(e, time),
+			(1, Some(p)) => p.input_event(e, time),
 			_ => {},
 		}
 	}
 	
-	pub fn render(&self) -> Vec<Vertex> {
+	fn pull_bridge(&mut self, player: usize) { //pulls the newest stretch of thread hanging between two platforms taut, turning it into a bridge
+		let time = self.time;
+		let p = match (player, &mut self.coop) {
+			(0, _) => &mut self.player,
+			(1, Some(c)) => c,
+			_ => return,
+		};
+		let platforms = &self.platforms;
+		let anchored = |t: &Thread| { //resting on a platform, nodes get pushed out to just touching so check a bit further than width
+			let r = vec2(t.width(), t.width()) * 2.0;
+			let node = AABB { pos: t.from - r, size: r * 2.0, vel: Vec2::zero() };
+			platforms.iter().any(|x| x.bridge.is_none() && node.penetration(x).is_some())
+		};
+		let mut ends = None;
+		let mut a = None;
+		for k in 0..p.thread.len() {
+			if !anchored(&p.thread[k]) {
+				continue;
+			}
+			match a {
+				Some(a) if k > a + 1 => { ends = Some((a, k)); break; }, //something sagging in between, so it's spanning a gap
+				_ => a = Some(k),
+			}
+		}
+		let (a, b) = match ends {
+			Some(x) => x,
+			None => return,
+		};
+		let (a_pos, b_pos) = (p.thread[a].from, p.thread[b].from);
+		let (left, right) = if a_pos.x < b_pos.x { (a_pos, b_pos) } else { (b_pos, a_pos) };
+		let dir = right - left;
+		let up = vec2(-dir.y, dir.x).normalize();
+		if dir.magnitude() > BRIDGE_MAX_LENGTH || dir.x < EPSILON || up.y < WALKABLE_SLOPE {
+			return; //too long or too steep to walk on
+		}
+		let t = up * BRIDGE_THICKNESS; //top of the bridge is where the thread was, it hangs down from there
+		let mut bridge = Platform::from_polygon(vec![left - t, right - t, right, left]);
+		bridge.collision = Collision::OneWay;
+		bridge.bridge = Some(Bridge { expires: time + BRIDGE_TICKS, durability: BRIDGE_DURABILITY });
+		p.thread.drain(a+1..b); //the sagging bit went into making the bridge, the older thread past it stays hooked on
+		p.thread[a+1].to = p.thread[a].from;
+		p.thread[a+1].length = (b_pos - a_pos).magnitude(); //lies along the top of the bridge
+		self.platforms.push(bridge);
+	}
+	
+	fn wear_bridges(&mut self, time: f64, delta: f64) { //bridges wear out under enemies, and snap if too many stand on one at once
+		for i in (0..self.platforms.len()).rev() {
+			let b = match self.platforms[i].bridge {
+				Some(x) => x,
+				None => continue,
+			};
+			let p = &self.platforms[i];
+			let standing = self.enemies.iter().filter(|e| {
+				let feet = AABB { pos: e.pos() - vec2(0.0, 0.01), size: e.size(), vel: Vec2::zero() };
+				e.centre_pos.y > p.centre_pos.y && feet.penetration(p).is_some()
+			}).count();
+			let durability = b.durability - standing as f64 * delta;
+			if time >= b.expires || durability <= 0.0 || standing > BRIDGE_MAX_ENEMIES {
+				self.platforms.remove(i);
+			} else {
+				self.platforms[i].bridge = Some(Bridge { durability, ..b });
+			}
+		}
+	}
+	
+	pub fn render(&self, alpha: f64) -> Vec<Vertex> { //alpha is how far we are between the previous tick and this one
 		let mut r = Vec::new(); let v = &mut r; 
-		self.player.render(v);
+		self.players().for_each(|x| x.render(v, alpha));
 		let player_hp_frac = self.player.hp / self.player.max_hp();
-		self.platforms.iter().for_each(|x| x.render(v, player_hp_frac));
-		self.enemies.iter().for_each(|x| x.render(v));
+		self.platforms.iter().for_each(|x| x.render(v, player_hp_frac, alpha));
+		self.hazards.iter().for_each(|x| x.render(v));
+		self.goal.iter().for_each(|x| x.render(v));
+		self.enemies.iter().for_each(|x| x.render(v, alpha));
+		self.projectiles.iter().for_each(|x| x.render(v, alpha));
+		if self.show_nav {
+			self.nav_for(EnemyKind::Walker).render(v);
+		}
 		r
 	}
+	
+	pub fn render_cam_pos(&self, alpha: f64) -> Vec2<f64> {
+		lerp(self.prev_cam_pos, self.cam_pos, alpha)
+	}
+	
+	pub fn render_time(&self, alpha: f64) -> f64 { //time of the previous tick is time - 1 since the loop always ticks with delta 1
+		self.time - 1.0 + alpha
+	}
 }
 
 pub trait BoundingBox {
 	fn pos(&self) -> Vec2<f64>;
 	fn size(&self) -> Vec2<f64>;
 	fn vel(&self) -> Vec2<f64>;
+	fn one_way(&self) -> bool { false } //only solid from above
+	fn polygon(&self) -> Option<Vec<Vec2<f64>>> { None } //world space convex outline, for things that aren't just their box
 	
 	fn centre_pos(&self) -> Vec2<f64> { self.pos() + self.size() / 2.0 }
 	fn left_edge(&self) -> f64 { self.pos().x - self.size().x }
@@ -94,6 +359,24 @@ pub trait BoundingBox {
 		]
 	}
 	
+	fn penetration(&self, other: &dyn BoundingBox) -> Option<Vec2<f64>> { //smallest move that gets self out of other, if they overlap
+		if let Some(polygon) = other.polygon() {
+			return sat_aabb_polygon(self.pos(), self.size(), &polygon).map(|(n, d)| n * d);
+		}
+		let (a, b) = (self.pos(), self.pos() + self.size());
+		let (c, d) = (other.pos(), other.pos() + other.size());
+		let overlap = vec2(b.x.min(d.x) - a.x.max(c.x), b.y.min(d.y) - a.y.max(c.y));
+		if overlap.x <= EPSILON || overlap.y <= EPSILON {
+			return None;
+		}
+		let dir = self.centre_pos() - other.centre_pos();
+		if overlap.x < overlap.y {
+			Some(vec2(if dir.x < 0.0 { -overlap.x } else { overlap.x }, 0.0))
+		} else {
+			Some(vec2(0.0, if dir.y < 0.0 { -overlap.y } else { overlap.y }))
+		}
+	}
+	
 	fn point_collides(&self, point: Vec2<f64>, velocity: Vec2<f64>) -> Option<Vec2<f64>> { //returns how much it collides by, if any
 		let p = point;
 		let v = velocity - self.vel();
@@ -109,6 +392,276 @@ pub trait BoundingBox {
 	}
 }
 
+#[derive(Debug,Default,Copy,Clone,PartialEq)]
+pub struct Sweep {
+	pub time: f64, //fraction of the movement done before touching, 0 to 1
+	pub normal: Vec2<f64>, //normal of the face that was hit, pointing back at whatever was moving
+}
+
+fn sweep_axis(min: f64, max: f64, movement: f64, other_min: f64, other_max: f64) -> Option<(f64, f64)> { //entry and exit times along one axis
+	if movement == 0.0 {
+		if min < other_max - EPSILON && max > other_min + EPSILON { //only counts as overlapping if actually inside, so sliding along a surface doesn't catch on it
+			Some((f64::NEG_INFINITY, f64::INFINITY))
+		} else {
+			None
+		}
+	} else if movement > 0.0 {
+		Some(((other_min - max) / movement, (other_max - min) / movement))
+	} else {
+		Some(((other_max - min) / movement, (other_min - max) / movement))
+	}
+}
+
+pub fn sweep_aabb(pos: Vec2<f64>, size: Vec2<f64>, movement: Vec2<f64>, other_pos: Vec2<f64>, other_size: Vec2<f64>) -> Option<Sweep> { //pos is the min corner, same as BoundingBox::pos
+	let (entry_x, exit_x) = sweep_axis(pos.x, pos.x + size.x, movement.x, other_pos.x, other_pos.x + other_size.x)?;
+	let (entry_y, exit_y) = sweep_axis(pos.y, pos.y + size.y, movement.y, other_pos.y, other_pos.y + other_size.y)?;
+	let entry = entry_x.max(entry_y);
+	let exit = exit_x.min(exit_y);
+	if entry > exit || entry < -EPSILON || entry > 1.0 { //already inside (that's for depenetration to sort out) or doesn't reach it this tick
+		return None;
+	}
+	let normal = if entry_x > entry_y { vec2(-movement.x.signum(), 0.0) } else { vec2(0.0, -movement.y.signum()) };
+	Some(Sweep { time: entry.max(0.0), normal })
+}
+
+fn segment_crosses_aabb(a: Vec2<f64>, b: Vec2<f64>, pos: Vec2<f64>, size: Vec2<f64>) -> bool { //slab test, clipping the segment to the box one axis at a time
+	let d = b - a;
+	let (mut t0, mut t1) = (0.0f64, 1.0f64);
+	for &(start, dir, min, max) in [(a.x, d.x, pos.x, pos.x + size.x), (a.y, d.y, pos.y, pos.y + size.y)].iter() {
+		if dir.abs() < EPSILON {
+			if start < min || start > max {
+				return false;
+			}
+		} else {
+			let (u, v) = ((min - start) / dir, (max - start) / dir);
+			t0 = t0.max(u.min(v));
+			t1 = t1.min(u.max(v));
+		}
+	}
+	t0 <= t1
+}
+
+pub fn closest_point_on_segment(p: Vec2<f64>, a: Vec2<f64>, b: Vec2<f64>) -> Vec2<f64> {
+	let d = b - a;
+	let l = dot(d, d);
+	let t = if l < EPSILON { 0.0 } else { (dot(p - a, d) / l).max(0.0).min(1.0) };
+	a + d * t
+}
+
+pub fn point_segment_distance(p: Vec2<f64>, a: Vec2<f64>, b: Vec2<f64>) -> f64 {
+	(p - closest_point_on_segment(p, a, b)).magnitude()
+}
+
+pub fn segment_intersection(a: Vec2<f64>, b: Vec2<f64>, c: Vec2<f64>, d: Vec2<f64>) -> Option<Vec2<f64>> { //where ab crosses cd, if it does
+	let cross = |u: Vec2<f64>, v: Vec2<f64>| u.x * v.y - u.y * v.x;
+	let (r, s) = (b - a, d - c);
+	let denom = cross(r, s);
+	if denom.abs() < EPSILON {
+		return None; //parallel, touching end to end doesn't count as a loop
+	}
+	let t = cross(c - a, s) / denom;
+	let u = cross(c - a, r) / denom;
+	if t >= 0.0 && t <= 1.0 && u >= 0.0 && u <= 1.0 { Some(a + r * t) } else { None }
+}
+
+pub fn point_in_polygon(p: Vec2<f64>, polygon: &[Vec2<f64>]) -> bool { //even-odd rule, so it works for loops that aren't convex
+	let mut inside = false;
+	for i in 0..polygon.len() {
+		let (a, b) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+		if (a.y > p.y) != (b.y > p.y) && p.x < a.x + (p.y - a.y) / (b.y - a.y) * (b.x - a.x) {
+			inside = !inside;
+		}
+	}
+	inside
+}
+
+pub fn polygon_area(polygon: &[Vec2<f64>]) -> f64 {
+	(0..polygon.len()).map(|i| {
+		let (a, b) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+		a.x * b.y - b.x * a.y
+	}).sum::<f64>().abs() / 2.0
+}
+
+pub fn segment_aabb_distance(a: Vec2<f64>, b: Vec2<f64>, pos: Vec2<f64>, size: Vec2<f64>) -> f64 { //0 if they touch
+	if segment_crosses_aabb(a, b, pos, size) {
+		return 0.0;
+	}
+	//when they don't touch, the closest points always include one of the segment's ends or one of the box's corners
+	let point_box = |p: Vec2<f64>| (p - vec2(p.x.max(pos.x).min(pos.x + size.x), p.y.max(pos.y).min(pos.y + size.y))).magnitude();
+	let corners = [pos, pos + vec2(size.x, 0.0), pos + size, pos + vec2(0.0, size.y)];
+	corners.iter().map(|&c| point_segment_distance(c, a, b)).fold(point_box(a).min(point_box(b)), f64::min)
+}
+
+#[derive(Debug,Copy,Clone)]
+pub struct Contact {
+	pub sweep: Sweep,
+	pub surface: f64, //position of the face that was hit, along the normal's axis
+	pub face: Vec2<f64>, //the face's own normal, slanted for polygons where sweep.normal is always along an axis
+	pub vel: Vec2<f64>,
+	pub is_enemy: bool,
+}
+
+impl Contact {
+	pub fn walkable(&self) -> bool { self.face.y >= WALKABLE_SLOPE }
+	pub fn steep(&self) -> bool { self.face.y > 0.0 && !self.walkable() } //floor too steep to stand on, we slide down it instead
+	
+	pub fn slide(&self, vel: Vec2<f64>) -> Vec2<f64> { //vel with the part going into the face taken away, relative to whatever we hit
+		let v = vel - self.vel;
+		v - self.face * dot(v, self.face).min(0.0) + self.vel
+	}
+}
+
+pub fn sweep_all<'a>(mover: &dyn BoundingBox, movement: Vec2<f64>, objects: impl Iterator<Item = (bool, &'a dyn BoundingBox)>) -> Vec<Contact> { //earliest contacts along movement, more than one if they happen at the same time
+	let mut r: Vec<Contact> = Vec::new();
+	for (is_enemy, object) in objects {
+		let rel = movement - object.vel();
+		let (sweep, surface, face) = match object.polygon() {
+			Some(polygon) => { //contact is given along whichever axis we're moving on, the same as for a box, so callers can treat them alike
+				let s = match sweep_polygon(mover.pos(), mover.size(), rel, &polygon) {
+					Some(s) if s.normal.y >= WALKABLE_SLOPE && movement.y == 0.0 => continue, //walking up a slope, stand_on_slopes lifts us onto it
+					Some(s) => s,
+					None => continue,
+				};
+				let normal = if rel.x.abs() > rel.y.abs() { vec2(-rel.x.signum(), 0.0) } else { vec2(0.0, -rel.y.signum()) };
+				let (a, b) = (mover.pos() + rel * s.time, mover.pos() + mover.size() + rel * s.time); //where we are when we touch it
+				let surface = if normal.x > 0.0 { a.x } else if normal.x < 0.0 { b.x } else if normal.y > 0.0 { a.y } else { b.y };
+				(Sweep { time: s.time, normal }, surface, s.normal)
+			},
+			None => {
+				let sweep = match sweep_aabb(mover.pos(), mover.size(), rel, object.pos(), object.size()) {
+					Some(x) => x,
+					None => continue,
+				};
+				let (a, b) = (object.pos(), object.pos() + object.size());
+				let surface = if sweep.normal.x > 0.0 { b.x } else if sweep.normal.x < 0.0 { a.x } else if sweep.normal.y > 0.0 { b.y } else { a.y };
+				(sweep, surface, sweep.normal)
+			},
+		};
+		if object.one_way() && sweep.normal.y <= 0.0 {
+			continue;
+		}
+		let c = Contact { sweep, surface, face, vel: object.vel(), is_enemy };
+		match r.first() {
+			Some(x) if sweep.time > x.sweep.time + EPSILON => {},
+			Some(x) if sweep.time < x.sweep.time - EPSILON => { r.clear(); r.push(c); },
+			_ => r.push(c),
+		}
+	}
+	r
+}
+
+fn project(points: &[Vec2<f64>], axis: Vec2<f64>) -> (f64, f64) {
+	points.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &p| (min.min(dot(p, axis)), max.max(dot(p, axis))))
+}
+
+fn sat_axes(polygon: &[Vec2<f64>]) -> Vec<Vec2<f64>> { //the box's axes then the polygon's outward edge normals
+	let mut axes = vec![vec2(1.0, 0.0), vec2(0.0, 1.0)];
+	for i in 0..polygon.len() {
+		let e = polygon[(i + 1) % polygon.len()] - polygon[i];
+		axes.push(vec2(e.y, -e.x).normalize());
+	}
+	axes
+}
+
+pub fn sweep_polygon(pos: Vec2<f64>, size: Vec2<f64>, movement: Vec2<f64>, polygon: &[Vec2<f64>]) -> Option<Sweep> { //swept separating axis test, normal is the face that was hit so it can be slanted
+	let corners = [pos, pos + vec2(size.x, 0.0), pos + size, pos + vec2(0.0, size.y)];
+	let (mut entry, mut exit, mut normal) = (f64::NEG_INFINITY, f64::INFINITY, Vec2::zero());
+	for axis in sat_axes(polygon) {
+		let (a0, a1) = project(&corners, axis);
+		let (b0, b1) = project(polygon, axis);
+		let m = dot(movement, axis);
+		let (t0, t1) = sweep_axis(a0, a1, m, b0, b1)?;
+		if t0 > entry {
+			entry = t0;
+			normal = if m > 0.0 { axis * -1.0 } else { axis };
+		}
+		exit = exit.min(t1);
+	}
+	if entry > exit || entry < -EPSILON || entry > 1.0 { //same as sweep_aabb
+		return None;
+	}
+	Some(Sweep { time: entry.max(0.0), normal })
+}
+
+pub fn sat_aabb_polygon(pos: Vec2<f64>, size: Vec2<f64>, polygon: &[Vec2<f64>]) -> Option<(Vec2<f64>, f64)> { //separating axis test, returns the direction to push the box out of the polygon and how far
+	let corners = [pos, pos + vec2(size.x, 0.0), pos + size, pos + vec2(0.0, size.y)];
+	let axes = sat_axes(polygon);
+	let mut best: Option<(Vec2<f64>, f64)> = None;
+	for axis in axes {
+		let (a0, a1) = project(&corners, axis);
+		let (b0, b1) = project(polygon, axis);
+		let (forwards, backwards) = (b1 - a0, a1 - b0); //how far the box has to go along +axis or -axis to get out
+		if forwards <= EPSILON || backwards <= EPSILON {
+			return None;
+		}
+		let (axis, d) = if forwards < backwards { (axis, forwards) } else { (axis * -1.0, backwards) };
+		if best.map(|x| d < x.1).unwrap_or(true) {
+			best = Some((axis, d));
+		}
+	}
+	best
+}
+
+pub fn stand_on_slopes<'a>(mover: &dyn BoundingBox, snap: f64, objects: impl Iterator<Item = (bool, &'a dyn BoundingBox)>) -> Option<f64> { //how far to move up (or down when snapping) to stand on a walkable polygon, if we're on one
+	let pos = mover.pos() - vec2(0.0, snap);
+	let mut r: Option<f64> = None;
+	for (_, object) in objects {
+		if let Some(polygon) = object.polygon() {
+			if let Some((n, d)) = sat_aabb_polygon(pos, mover.size(), &polygon) {
+				if n.y >= WALKABLE_SLOPE && !(object.one_way() && d / n.y - snap > ONE_WAY_CATCH) {
+					let lift = d / n.y - snap; //push straight up rather than along the normal, so we don't slide down
+					r = Some(r.map(|x| x.max(lift)).unwrap_or(lift));
+				}
+			}
+		}
+	}
+	r
+}
+
+pub fn depenetrate<'a>(mover: &dyn BoundingBox, objects: impl Iterator<Item = (bool, &'a dyn BoundingBox)>) -> (Vec2<f64>, bool) { //how far to move to not be inside anything, and whether we were inside an enemy
+	let objects = objects.collect::<Vec<_>>(); //give enemies before platforms so platforms get the final say and we don't get pushed into the floor
+	let mut aabb = AABB { pos: mover.pos(), size: mover.size(), vel: mover.vel() };
+	let mut hit_enemy = false;
+	for _ in 0..DEPENETRATION_ITERATIONS {
+		let mut moved = false;
+		for &(is_enemy, object) in objects.iter().filter(|x| !x.1.one_way()) { //being inside a one way platform is fine, that's how you jump through them
+			if let Some(mtv) = aabb.penetration(object) {
+				aabb.pos += mtv;
+				hit_enemy |= is_enemy;
+				moved = true;
+			}
+		}
+		if !moved {
+			break;
+		}
+	}
+	(aabb.pos - mover.pos(), hit_enemy)
+}
+
+pub fn is_riding(character: &dyn BoundingBox, platform: &dyn BoundingBox) -> bool { //standing on top of platform
+	let (a, b) = (character.pos(), character.pos() + character.size());
+	let (c, d) = (platform.pos(), platform.pos() + platform.size());
+	(a.y - d.y).abs() < RIDING_TOLERANCE && a.x < d.x - EPSILON && b.x > c.x + EPSILON
+}
+
+pub fn carry(character: &dyn BoundingBox, riding: bool, platforms: &[Platform], i: usize, broadphase: &Broadphase) -> (Vec2<f64>, bool) { //how far platform i moving takes character, and whether it got crushed against something near it
+	let mut aabb = AABB { pos: character.pos(), size: character.size(), vel: character.vel() };
+	if riding {
+		aabb.pos += platforms[i].vel;
+	}
+	if let Some(mtv) = aabb.penetration(&platforms[i]).filter(|_| !platforms[i].one_way()) {
+		aabb.pos += mtv;
+	}
+	let solid = |aabb: &AABB| broadphase.platforms.query(aabb.pos, aabb.size).into_iter().filter(|&j| j < platforms.len() && !platforms[j].one_way()).collect::<Vec<_>>();
+	for j in solid(&aabb).into_iter().filter(|&j| j != i) {
+		if let Some(mtv) = aabb.penetration(&platforms[j]) {
+			aabb.pos += mtv;
+		}
+	}
+	let crushed = solid(&aabb).into_iter().any(|j| aabb.penetration(&platforms[j]).is_some()); //got pushed out of one thing straight into another
+	(aabb.pos - character.pos(), crushed)
+}
+
 pub trait Collider<T> {
 	fn collides(&self, other: &T) -> bool;
 }
@@ -123,7 +676,7 @@ impl<A: BoundingBox, B: BoundingBox> Collider<A> for B {
 	}
 }
 
-/*#[derive(Debug,Default,Copy,Clone)]
+#[derive(Debug,Default,Copy,Clone)]
 pub struct AABB {
 	pub pos: Vec2<f64>,
 	pub size: Vec2<f64>,
@@ -134,11 +687,12 @@ impl BoundingBox for AABB {
 	fn pos(&self) -> Vec2<f64> { self.pos }
 	fn size(&self) -> Vec2<f64> { self.size }
 	fn vel(&self) -> Vec2<f64> { self.vel }
-}*/
+}
 
-#[derive(Debug,Default)]
+#[derive(Debug,Default,Clone)]
 pub struct Player {
 	pub centre_pos: Vec2<f64>,
+	pub prev_pos: Vec2<f64>, //centre_pos at the start of the last tick, for render interpolation
 	pub vel: Vec2<f64>,
 	pub movement: [bool; 4],
 	pub grounded: f64,
@@ -146,6 +700,22 @@ pub struct Player {
 	pub thread: Vec<Thread>,
 	pub bob: Vec2<f64>, //to make thread wave up and down/left and right
 	pub bob_dir: Vec2<i8>,
+	pub explosion: Explosion, //what our detonating thread does
+	pub blasts: Vec<(Vec2<f64>, f64)>, //where recent explosions went off and how many ticks ago, only for drawing
+	pub grapple: Option<Grapple>,
+	pub reeling: bool,
+}
+
+#[derive(Debug,Default,Copy,Clone)]
+pub struct Grapple { //needle on the end of a thread, once it sticks in something we swing from it
+	pub needle: Vec2<f64>,
+	pub vel: Vec2<f64>, //zero once it's stuck
+	pub length: f64, //how far the thread lets us get from the needle once it's stuck
+	pub fired: f64,
+}
+
+impl Grapple {
+	pub fn anchored(&self) -> bool { self.vel.x == 0.0 && self.vel.y == 0.0 }
 }
 
 impl BoundingBox for Player {
@@ -162,112 +732,256 @@ impl Player {
 		r
 	}
 	
-	pub fn move_speed(&self) -> f64 { if time() - self.grounded < 1.1 { 0.005 } else { 0.0025 } }
+	pub fn ground_speed(&self) -> f64 { 0.005 }
+	pub fn air_speed(&self) -> f64 { 0.0025 }
+	pub fn move_speed(&self, time: f64) -> f64 { if time - self.grounded < 1.1 { self.ground_speed() } else { self.air_speed() } }
 	pub fn jump_vel(&self) -> f64 { 0.13 }
 	pub fn max_hp(&self) -> f64 { 30.0 }
 	pub fn grounded_limit(&self) -> f64 { 5.0 } //time since touching a platform that we're still allowed to jump
 	pub fn bob_rate(&self) -> Vec2<f64> { vec2(0.05,0.025) }
 	pub fn bob_max(&self) -> Vec2<f64> { vec2(0.025,0.025) }
 	
-	pub fn detonate_thread(&mut self) {
+	pub fn spawn(pos: Vec2<f64>) -> Self {
+		let mut r = Self::new();
+		r.centre_pos = pos;
+		r.prev_pos = pos;
+		r
+	}
+	
+	pub fn input_event(&mut self, e: InputEvent, time: f64) {
+		use InputEvent::*;
+		match e {
+			LeftClick(target) => match self.grapple {
+				Some(_) => self.grapple = None, //clicking again lets go
+				None => {
+					let dir = target - self.centre_pos;
+					if dir.magnitude() > EPSILON {
+						self.grapple = Some(Grapple { needle: self.centre_pos, vel: dir.normalize() * NEEDLE_SPEED, length: 0.0, fired: time });
+					}
+				},
+			},
+			KeyDown(k) => match k.as_str() {
+				"r" => self.reeling = true,
+				"d" | "ArrowRight" => self.movement[0] = true,
+				"a" | "ArrowLeft" => self.movement[1] = true,
+				"w" | "ArrowUp" => self.movement[2] = true,
+				"s" | "ArrowDown" => self.movement[3] = true,
+				"x" => self.detonate_thread(time),
+				_ => {},
+			}
+			KeyUp(k) => match k.as_str() {
+				"d" | "ArrowRight" => self.movement[0] = false,
+				"a" | "ArrowLeft" => self.movement[1] = false,
+				"w" | "ArrowUp" => self.movement[2] = false,
+				"s" | "ArrowDown" => self.movement[3] = false,
+				"r" => self.reeling = false,
+				_ => {},
+			}
+			_ => {},
+		}
+	}
+	
+	pub fn detonate_thread(&mut self, time: f64) {
 		if !self.thread[self.thread.len()-1].detonated.is_some() {
-			self.thread.iter_mut().enumerate().for_each(|(i, x)| x.detonate(i));
+			self.thread.iter_mut().enumerate().for_each(|(i, x)| x.detonate(i, time));
 		}
 	}
 	
-	pub fn render(&self, v: &mut Vec<Vertex>) {
-		quad(v, self.pos(), 100, self.size(), [[1.0,1.0,1.0,1.0]; 4], [[0.0; 4]; 4], [[0.0; 3]; 4], 0.0, Default::default());
+	fn push_out(&mut self, correction: Vec2<f64>, time: f64) {
+		self.centre_pos += correction;
+		if correction.x * self.vel.x < 0.0 { self.vel.x = 0.0; }
+		if correction.y * self.vel.y < 0.0 { self.vel.y = 0.0; }
+		if correction.y > correction.magnitude() * WALKABLE_SLOPE { self.grounded = time; } //pushed out of something steep isn't standing on it
+	}
+	
+	fn swing(&mut self, platforms: &[Platform], broadphase: &Broadphase, delta: f64) { //moves the needle if it's flying, and keeps us on the end of its thread if it's stuck
+		let g = match &mut self.grapple {
+			Some(x) => x,
+			None => return,
+		};
+		if !g.anchored() {
+			for _ in 0..NEEDLE_SUBSTEPS {
+				g.needle += g.vel * (delta / NEEDLE_SUBSTEPS as f64);
+				let tip = AABB { pos: g.needle - vec2(0.001, 0.001), size: vec2(0.002, 0.002), vel: Vec2::zero() };
+				let hit = broadphase.platforms.query(tip.pos, tip.size).into_iter().any(|i| i < platforms.len() && tip.penetration(&platforms[i]).is_some());
+				if hit {
+					g.vel = Vec2::zero();
+					g.length = (self.centre_pos - g.needle).magnitude().max(MIN_GRAPPLE_LENGTH);
+					break;
+				}
+			}
+			if !g.anchored() && (g.needle - self.centre_pos).magnitude() > NEEDLE_RANGE {
+				self.grapple = None; //missed
+			}
+			return;
+		}
+		if self.reeling {
+			g.length = (g.length - REEL_SPEED * delta).max(MIN_GRAPPLE_LENGTH);
+		}
+		let gravity = vec2(0.0, -GRAVITY); //gets added after the x sweep, so account for it now
+		let d = self.centre_pos + self.vel + gravity - g.needle;
+		if d.magnitude() > g.length {
+			self.vel = g.needle + d.normalize() * g.length - self.centre_pos - gravity;
+		}
+	}
+	
+	fn close_loops(&mut self, enemies: &mut [Enemy], broadphase: &Broadphase, time: f64) { //if the newest segment crossed an older one, stitch every enemy inside the loop it made
+		let (a, b) = (self.thread[0].from, self.thread[0].to);
+		for k in 2..self.thread.len() { //thread[1] shares an end with thread[0], so it can't make a loop
+			if let Some(p) = segment_intersection(a, b, self.thread[k].from, self.thread[k].to) {
+				let mut polygon = vec![p];
+				polygon.extend(self.thread[..k].iter().map(|x| x.from));
+				let area = polygon_area(&polygon);
+				let min = polygon.iter().fold(vec2(f64::INFINITY, f64::INFINITY), |a, p| vec2(a.x.min(p.x), a.y.min(p.y)));
+				let max = polygon.iter().fold(vec2(f64::NEG_INFINITY, f64::NEG_INFINITY), |a, p| vec2(a.x.max(p.x), a.y.max(p.y)));
+				for i in broadphase.enemies.query(min, max - min) {
+					if i < enemies.len() && point_in_polygon(enemies[i].centre_pos, &polygon) {
+						enemies[i].stitch(area, time);
+					}
+				}
+				return; //the newest crossing makes the tightest loop, older ones would just contain it
+			}
+		}
+	}
+	
+	pub fn render(&self, v: &mut Vec<Vertex>, alpha: f64) {
+		let pos = lerp(self.prev_pos, self.centre_pos, alpha);
+		quad(v, pos - self.size() / 2.0, 100, self.size(), [[1.0,1.0,1.0,1.0]; 4], [[0.0; 4]; 4], [[0.0; 3]; 4], 0.0, Default::default());
 		for i in 0..self.thread.len().saturating_sub(1) {
-			self.thread[i].render(v);
+			if i == 0 { //the newest segment is pinned to us, so it has to follow us between ticks too or it comes away from the body
+				Thread { to: self.thread[0].to + pos - self.centre_pos, ..self.thread[0] }.render(v);
+			} else {
+				self.thread[i].render(v);
+			}
+		}
+		if let Some(g) = &self.grapple { //the needle's thread looks like any other
+			Thread::new(g.needle, pos, g.fired, g.fired - 1.0).render(v);
+		}
+		for &(pos, age) in self.blasts.iter() {
+			let t = (age / BLAST_TICKS) as f32;
+			let size = vec2(1.0, 1.0) * self.explosion.radius * 2.0 * (0.5 + age / BLAST_TICKS * 0.5);
+			quad(v, pos - size / 2.0, 11, size, [[1.0,0.6,0.3,0.5 * (1.0 - t)]; 4], [[0.0; 4]; 4], [[0.0; 3]; 4], 0.0, Default::default());
 		}
 	}
 	
-	pub fn do_movement(&mut self, platforms: &Vec<Platform>, enemies: &mut Vec<Enemy>, delta: f64) {
+	pub fn do_movement(&mut self, platforms: &[Platform], enemies: &mut Vec<Enemy>, projectiles: &mut Vec<Projectile>, broadphase: &Broadphase, delta: f64, time: f64, rng: &mut dyn Rng) {
 		let old_pos = self.centre_pos;
 		let mut damaged = false;
 		let movement_x = self.movement[0] as u8 as f64 - self.movement[1] as u8 as f64;
-		self.vel.x += movement_x * self.move_speed();
+		self.vel.x += movement_x * self.move_speed(time);
+		self.swing(platforms, broadphase, delta); //before the sweeps, so the rope only ever changes where we're trying to go and collisions still get the final say
+		let contacts = sweep_all(self, vec2(self.vel.x, 0.0), broadphase.nearby(platforms, enemies, self, vec2(self.vel.x, 0.0), None).into_iter());
 		self.centre_pos.x += self.vel.x;
-		for (is_enemy, object) in enemies.iter().map(|x| (true, x as &dyn BoundingBox)).chain(platforms.iter().map(|x| (false, x as &dyn BoundingBox))) { //collide enemies first so player doesn't get scronched into the floor
-			let p = self.collision_point_x(object.vel());
-			if let Some(correction) = object.point_collides(p, self.vel()) {
-				if is_enemy { damaged = true; }
-				let diff = (self.vel() - object.vel()).x;
-				self.centre_pos.x -= diff.signum() * correction.x;
-				self.vel.y -= self.vel.y.signum() * (diff.abs() * FRICTION).min(self.vel.y.abs());
-				self.vel.x = object.vel().x;
-			}
+		if let Some(c) = contacts.first() {
+			self.centre_pos.x = c.surface + c.sweep.normal.x * self.size().x / 2.0;
+			let diff = (self.vel() - c.vel).x;
+			self.vel.y -= self.vel.y.signum() * (diff.abs() * FRICTION).min(self.vel.y.abs());
+			self.vel.x = c.vel.x;
+		}
+		if contacts.iter().any(|c| c.is_enemy) {
+			damaged = true;
 		}
 		self.vel.y -= GRAVITY;
-		if time() - self.grounded < self.grounded_limit() && self.movement[2] {
+		if time - self.grounded < self.grounded_limit() && self.movement[2] {
 			self.vel.y += self.jump_vel();
 			self.grounded = -self.grounded_limit();
 		}
+		let drop_through = self.movement[3]; //holding down falls through one way platforms
+		let contacts = sweep_all(self, vec2(0.0, self.vel.y), broadphase.nearby(platforms, enemies, self, vec2(0.0, self.vel.y), None).into_iter().filter(|x| !(drop_through && x.1.one_way())));
 		self.centre_pos.y += self.vel.y;
-		for (is_enemy, object) in enemies.iter().map(|x| (true, x as &dyn BoundingBox)).chain(platforms.iter().map(|x| (false, x as &dyn BoundingBox))) {
-			let p = self.collision_point_y(object.vel());
-			if let Some(correction) = object.point_collides(p, self.vel()) {
-				if (self.vel - object.vel()).y.is_sign_negative() {
-					self.grounded = time();
-				} else if is_enemy { //only do damage when enemy on head, not when on head of enemy
-					damaged = true;
-				}
-				let diff = (self.vel() - object.vel()).y;
-				self.centre_pos.y -= diff.signum() * correction.y;
+		if let Some(c) = contacts.first() {
+			self.centre_pos.y = c.surface + c.sweep.normal.y * self.size().y / 2.0;
+			if c.steep() {
+				self.vel = c.slide(self.vel);
+			} else {
+				let diff = (self.vel() - c.vel).y;
 				self.vel.x -= self.vel.x.signum() * (diff.abs() * FRICTION).min(self.vel.x.abs());
-				self.vel.y = object.vel().y;
+				self.vel.y = c.vel.y;
 			}
 		}
-		//TODO: this doesn't work
-		//if still stuck in something, move upwards
-		/*for object in enemies.iter().map(|x| x as &BoundingBox).chain(platforms.iter().map(|x| x as &BoundingBox)) {
-			let p = self.all_collision_points();
-			if p.iter().any(|&p| object.point_collides(p, self.vel()).is_some()) {
-				self.centre_pos.y = object.top_edge() + self.size().y / 2.0;
+		for c in contacts.iter() {
+			if c.walkable() {
+				self.grounded = time;
+			} else if c.is_enemy { //only do damage when enemy on head, not when on head of enemy
+				damaged = true;
 			}
-		}*/
+		}
+		let snap = if time - self.grounded < 1.1 && self.vel.y <= 0.0 { self.vel.x.abs() * 2.0 } else { 0.0 }; //stick to slopes when walking down them instead of bouncing off
+		if let Some(lift) = stand_on_slopes(self, snap, broadphase.nearby(platforms, enemies, self, vec2(0.0, -snap), None).into_iter().filter(|x| !(drop_through && x.1.one_way()))) {
+			self.centre_pos.y += lift;
+			self.vel.y = self.vel.y.max(0.0);
+			self.grounded = time;
+		}
+		//if still stuck in something (usually an enemy walked into us), push out the shortest way
+		let (correction, hit_enemy) = depenetrate(self, broadphase.nearby(platforms, enemies, self, Vec2::zero(), None).into_iter());
+		self.push_out(correction, time);
+		if hit_enemy {
+			damaged = true;
+		}
 		//let v = self.vel.magnitude();
 		//self.vel *= 1.0 / (v * DRAG + 1.0);
-		self.vel /= self.vel.abs() * DRAG + 1.0; //do drag on both axes seperately because... it feels better? idk
+		let drag = if self.grapple.map(|x| x.anchored()).unwrap_or(false) { DRAG * 0.1 } else { DRAG }; //swinging would barely move otherwise
+		self.vel /= self.vel.abs() * drag + 1.0; //do drag on both axes seperately because... it feels better? idk
 		self.hp += 0.05;
 		if damaged {
 			self.hp -= 1.0;
 		}
 		if self.thread.is_empty() {
-			self.thread.push(Thread::new(old_pos, self.centre_pos + self.bob, time(), time()-1.0));
+			self.thread.push(Thread::new(old_pos, self.centre_pos + self.bob, time, time-1.0));
 		}
 		if self.vel.magnitude() > 0.001 {
-			self.thread.insert(0, Thread::new(self.thread[0].to, self.centre_pos + self.bob, time(), self.thread[0].time));
+			self.thread.insert(0, Thread::new(self.thread[0].to, self.centre_pos + self.bob, time, self.thread[0].time));
+			self.close_loops(enemies, broadphase, time);
 		}
-		let mut enemies_hit = HashSet::new();
-		while !self.thread.is_empty() && (self.thread[self.thread.len() - 1].time + 500.0 < time() || self.thread[self.thread.len() - 1].detonated.map(|x| x < time()).unwrap_or(false)) {
+		let mut blasts = Vec::new(); //segments going off this tick, the detonate stagger makes them go off in a wave along the thread
+		while !self.thread.is_empty() && (self.thread[self.thread.len() - 1].time + 500.0 < time || self.thread[self.thread.len() - 1].detonated.map(|x| x < time).unwrap_or(false)) {
 			let thread = self.thread.pop().unwrap();
 			if thread.detonated.is_some() {
-				for i in 0..enemies.len() {
-					if !enemies_hit.contains(&i) && thread.collides(&enemies[i]) {
-						enemies_hit.insert(i);
-					}
-				}
+				blasts.push(thread);
 			}
 		}
 		if self.thread.is_empty() {
-			self.thread.push(Thread::new(old_pos, self.centre_pos + self.bob, time(), time()-1.0));
+			self.thread.push(Thread::new(old_pos, self.centre_pos + self.bob, time, time-1.0));
+		}
+		let e = self.explosion;
+		let mut enemies_hit = std::collections::BTreeMap::new(); //strongest blast each enemy got caught in, and which way it pushes them
+		let mut self_hit = 0.0f64;
+		for thread in blasts.iter() {
+			let r = vec2(e.radius, e.radius).max(vec2(thread.width(), thread.width()));
+			let (pos, size) = swept_bounds(thread.from - r, r * 2.0, thread.to - thread.from);
+			for i in broadphase.enemies.query(pos, size) {
+				if i >= enemies.len() {
+					continue;
+				}
+				let strength = if thread.collides(&enemies[i]) { 1.0 } else { e.strength(segment_aabb_distance(thread.from, thread.to, enemies[i].pos(), enemies[i].size())) };
+				if strength > enemies_hit.get(&i).map(|x: &(f64, Vec2<f64>)| x.0).unwrap_or(0.0) {
+					let c = enemies[i].centre_pos;
+					let dir = c - closest_point_on_segment(c, thread.from, thread.to);
+					let dir = if dir.magnitude() < EPSILON { vec2(0.0, 1.0) } else { dir.normalize() };
+					enemies_hit.insert(i, (strength, dir));
+				}
+			}
+			projectiles.retain(|p| point_segment_distance(p.centre_pos, thread.from, thread.to) > r.x); //thread going off takes out anything flying through it
+			self_hit = self_hit.max(e.strength(segment_aabb_distance(thread.from, thread.to, self.pos(), self.size())));
+			self.blasts.push(((thread.from + thread.to) / 2.0, 0.0));
 		}
-		let mut enemies_hit = enemies_hit.iter().cloned().collect::<Vec<_>>();
-		enemies_hit.sort();
-		for &i in enemies_hit.iter().rev() {
-			enemies[i].hp -= binomial(3) * 10.0;
+		for (&i, &(strength, dir)) in enemies_hit.iter().rev() { //backwards so removing doesn't move the ones still to do
+			enemies[i].hp -= rng.binomial(3) * e.damage * strength;
+			if enemies[i].stitched() { //the stitches go off with the thread
+				enemies[i].hp -= enemies[i].stitched_area * e.stitch_bonus * strength;
+				enemies[i].stitched_area = 0.0;
+			}
+			enemies[i].vel += dir * e.knockback * strength;
 			if enemies[i].hp <= 0.0 {
 				enemies.remove(i);
 			}
 		}
-		for i in 1..(self.thread.len()-1) {
-			let parent = self.thread[i+1];
-			let child = self.thread[i-1];
-			self.thread[i].do_movement(delta, parent, child, self.centre_pos);
-		}
+		self.hp -= self_hit * e.damage * e.self_damage;
+		self.blasts.iter_mut().for_each(|x| x.1 += delta);
+		self.blasts.retain(|x| x.1 < BLAST_TICKS);
+		Thread::simulate(&mut self.thread, self.centre_pos + self.bob, platforms, broadphase, delta);
 		self.bob += self.bob_dir.f64()
-			* vec2(binomial(2), binomial(2))
+			* vec2(rng.binomial(2), rng.binomial(2))
 			* self.bob_rate()
 			* self.vel.magnitude().sqrt()
 			* ((self.bob_max().max(self.bob.abs()) * 1.2 - self.bob.abs()) / self.bob_max());
@@ -275,90 +989,293 @@ impl Player {
 		if self.vel.magnitude().sqrt() < 0.0001 && self.bob.y > -self.bob_max().y * 1.5 {
 			self.bob.y -= self.thread[self.thread.len()-1].fall_rate(delta, self.centre_pos);
 		}
-		let n = 10.0;
-		uniforms().set_cam_pos((uniforms().cam_pos * n + self.centre_pos) / (n+1.0));
 	}
 }
 
-#[derive(Debug,Default)]
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub enum Collision {
+	Solid,
+	OneWay, //can jump up through it and drop down through it by holding down
+}
+
+impl Default for Collision {
+	fn default() -> Self { Collision::Solid }
+}
+
+#[derive(Debug,Default,Clone)]
 pub struct Platform {
 	pub centre_pos: Vec2<f64>,
 	pub size: Vec2<f64>,
+	pub collision: Collision,
+	pub vel: Vec2<f64>, //how far it moved last tick, only set by update
+	pub path: Option<PlatformPath>,
+	pub shape: Option<Vec<Vec2<f64>>>, //convex outline relative to centre_pos, for slopes etc, size should be its bounds
+	pub bridge: Option<Bridge>, //made out of thread, goes away eventually
+}
+
+#[derive(Debug,Default,Copy,Clone)]
+pub struct Bridge {
+	pub expires: f64,
+	pub durability: f64, //goes down while enemies stand on it, breaks at 0
 }
 
 impl BoundingBox for Platform {
 	fn pos(&self) -> Vec2<f64> { self.centre_pos - self.size() / 2.0 }
 	fn size(&self) -> Vec2<f64> { self.size }
-	fn vel(&self) -> Vec2<f64> { Vec2::zero() }
+	fn vel(&self) -> Vec2<f64> { self.vel }
+	fn one_way(&self) -> bool { self.collision == Collision::OneWay }
+	fn polygon(&self) -> Option<Vec<Vec2<f64>>> { self.shape.as_ref().map(|x| x.iter().map(|&p| p + self.centre_pos).collect()) }
 }
 
 impl Platform {
-	pub fn render(&self, v: &mut Vec<Vertex>, player_hp_frac: f64) {
-		quad(v, self.pos(), 0, self.size(), [[(1.0 - player_hp_frac as f32) / 2.0,0.0,0.0,1.0]; 4], [[0.0; 4]; 4], [[0.0; 3]; 4], 0.0, Default::default());
+	pub fn from_polygon(points: Vec<Vec2<f64>>) -> Self { //points are in world space and should go anticlockwise
+		let min = points.iter().fold(vec2(f64::INFINITY, f64::INFINITY), |a, p| vec2(a.x.min(p.x), a.y.min(p.y)));
+		let max = points.iter().fold(vec2(f64::NEG_INFINITY, f64::NEG_INFINITY), |a, p| vec2(a.x.max(p.x), a.y.max(p.y)));
+		let centre_pos = (min + max) / 2.0;
+		Self {
+			centre_pos,
+			size: max - min,
+			shape: Some(points.iter().map(|&p| p - centre_pos).collect()),
+			.. Default::default()
+		}
+	}
+	
+	pub fn update(&mut self, time: f64) {
+		if let Some(path) = &self.path {
+			let pos = path.pos_at(time);
+			self.vel = pos - self.centre_pos;
+			self.centre_pos = pos;
+		}
+	}
+	
+	pub fn render(&self, v: &mut Vec<Vertex>, player_hp_frac: f64, alpha: f64) {
+		let a = if self.one_way() { 0.5 } else { 1.0 };
+		let centre_pos = lerp(self.centre_pos - self.vel, self.centre_pos, alpha);
+		let color = [(1.0 - player_hp_frac as f32) / 2.0,0.0,0.0,a];
+		if let (Some(shape), Some(b)) = (&self.shape, &self.bridge) { //looks like the thread it was made from, fading as it wears out
+			let points = shape.iter().map(|&p| p + centre_pos).collect::<Vec<_>>();
+			let a = (0.4 + 0.6 * b.durability / BRIDGE_DURABILITY) as f32;
+			polygon(v, &points, 0, [0.7,0.7,0.7,a], [0.6,0.7,1.0,0.7], [0.0,0.25,0.5], 0.025, [0.1,0.1,0.1]);
+		} else if let Some(shape) = &self.shape {
+			let points = shape.iter().map(|&p| p + centre_pos).collect::<Vec<_>>();
+			polygon(v, &points, 0, color, [0.0; 4], [0.0; 3], 0.0, Default::default());
+		} else {
+			quad(v, centre_pos - self.size() / 2.0, 0, self.size(), [color; 4], [[0.0; 4]; 4], [[0.0; 3]; 4], 0.0, Default::default());
+		}
+	}
+}
+
+#[derive(Debug,Default,Clone)]
+pub struct Hazard {
+	pub centre_pos: Vec2<f64>,
+	pub size: Vec2<f64>,
+	pub damage: f64, //per tick
+}
+
+impl BoundingBox for Hazard {
+	fn pos(&self) -> Vec2<f64> { self.centre_pos - self.size() / 2.0 }
+	fn size(&self) -> Vec2<f64> { self.size }
+	fn vel(&self) -> Vec2<f64> { Vec2::zero() }
+}
+
+impl Hazard {
+	pub fn render(&self, v: &mut Vec<Vertex>) {
+		quad(v, self.pos(), 2, self.size(), [[1.0,0.5,0.0,0.8]; 4], [[1.0,0.9,0.3,0.5]; 4], [[0.0; 3]; 4], 0.05, Default::default());
+	}
+}
+
+#[derive(Debug,Default,Clone)]
+pub struct Goal { //where the level ends
+	pub centre_pos: Vec2<f64>,
+	pub size: Vec2<f64>,
+}
+
+impl BoundingBox for Goal {
+	fn pos(&self) -> Vec2<f64> { self.centre_pos - self.size() / 2.0 }
+	fn size(&self) -> Vec2<f64> { self.size }
+	fn vel(&self) -> Vec2<f64> { Vec2::zero() }
+}
+
+impl Goal {
+	pub fn render(&self, v: &mut Vec<Vertex>) {
+		quad(v, self.pos(), 2, self.size(), [[0.3,1.0,0.5,0.6]; 4], [[0.8,1.0,0.8,0.6]; 4], [[0.0; 3]; 4], 0.02, Default::default());
 	}
 }
 
-#[derive(Debug,Default)]
+#[derive(Debug,Default,Clone)]
+pub struct Spawner {
+	pub pos: Vec2<f64>,
+	pub kind: EnemyKind,
+	pub interval: f64, //before the wave director speeds it up
+	pub cap: usize, //won't spawn while this many of its enemies are still alive, the wave director raises it over time
+	pub next_spawn: f64,
+}
+
+impl Spawner {
+	pub fn new(pos: Vec2<f64>, kind: EnemyKind, interval: f64, cap: usize) -> Self {
+		Self {
+			pos,
+			kind,
+			interval,
+			cap,
+			next_spawn: interval,
+		}
+	}
+	
+	pub fn spawn(&mut self, time: f64, alive: usize, waves: &mut WaveDirector) -> Option<Enemy> { //alive is how many enemies this spawner made that are still around
+		if time < self.next_spawn {
+			return None;
+		}
+		self.next_spawn += waves.next_interval(self.interval, time); //even when capped, so the schedule doesn't depend on what's still alive
+		if alive >= waves.cap(self.cap, time) {
+			return None;
+		}
+		let mut e = Enemy {
+			centre_pos: self.pos,
+			prev_pos: self.pos,
+			toughness: waves.toughness(time),
+			.. Enemy::new(self.kind, time)
+		};
+		e.hp = e.max_hp();
+		Some(e)
+	}
+}
+
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub enum EnemyKind {
+	Walker, //follows the nav graph on foot
+	Flyer, //ignores gravity and heads straight for the player
+	Shooter, //walks like a walker but keeps its distance and fires projectiles
+}
+
+impl Default for EnemyKind {
+	fn default() -> Self { EnemyKind::Walker }
+}
+
+#[derive(Debug,Default,Clone)]
 pub struct Enemy {
+	pub kind: EnemyKind,
 	pub centre_pos: Vec2<f64>,
+	pub prev_pos: Vec2<f64>,
 	pub vel: Vec2<f64>,
 	pub grounded: f64,
 	pub start_time: f64,
 	pub hp: f64,
+	pub stitched_until: f64, //stunned until this time after being caught in a loop of thread
+	pub stitched_area: f64, //area of the loop that caught us, 0 when not stitched
+	pub ai: AiState,
+	pub ai_dir: f64, //which way we're patrolling
+	pub ai_since: f64, //when we went into the current state
+	pub fired: f64, //when a shooter last fired
+	pub toughness: f64, //multiplies max hp, goes up with each wave
+	pub spawner: Option<usize>, //index of the spawner that made us, for its cap
 }
 
 impl BoundingBox for Enemy {
 	fn pos(&self) -> Vec2<f64> { self.centre_pos - self.size() / 2.0 }
-	fn size(&self) -> Vec2<f64> { vec2(0.08,0.15) }
+	fn size(&self) -> Vec2<f64> {
+		match self.kind {
+			EnemyKind::Walker => vec2(0.08,0.15),
+			EnemyKind::Flyer => vec2(0.08,0.08),
+			EnemyKind::Shooter => vec2(0.1,0.12),
+		}
+	}
 	fn vel(&self) -> Vec2<f64> { self.vel }
 }
 
 impl Enemy {
-	pub fn new() -> Self {
-		Self {
-			start_time: time(),
-			hp: Self::max_hp(),
+	pub fn new(kind: EnemyKind, time: f64) -> Self {
+		let mut r = Self {
+			kind,
+			start_time: time,
+			fired: time, //no shooting the moment we appear
+			toughness: 1.0,
 			.. Default::default()
+		};
+		r.hp = r.max_hp();
+		r
+	}
+	
+	pub fn max_hp(&self) -> f64 {
+		self.toughness * match self.kind {
+			EnemyKind::Walker => 25.0,
+			EnemyKind::Flyer => 15.0,
+			EnemyKind::Shooter => 20.0,
 		}
 	}
 	
-	fn max_hp() -> f64 {
-		25.0
+	pub fn ground_speed() -> f64 { 0.0025 }
+	pub fn air_speed() -> f64 { 0.00125 }
+	pub fn move_speed(&self, time: f64) -> f64 { if time - self.grounded < 1.1 { Self::ground_speed() } else { Self::air_speed() } }
+	
+	fn push_out(&mut self, correction: Vec2<f64>, time: f64) {
+		self.centre_pos += correction;
+		if correction.x * self.vel.x < 0.0 { self.vel.x = 0.0; }
+		if correction.y * self.vel.y < 0.0 { self.vel.y = 0.0; }
+		if correction.y > correction.magnitude() * WALKABLE_SLOPE { self.grounded = time; } //pushed out of something steep isn't standing on it
 	}
 	
-	pub fn move_speed(&self) -> f64 { if time() - self.grounded < 1.1 { 0.0025 } else { 0.00125 } }
+	pub fn stitched(&self) -> bool { self.stitched_area > 0.0 }
 	
-	pub fn do_movement(&mut self, player: &mut Player, platforms: &Vec<Platform>, enemies: &Vec<Enemy>) {
-		let movement_x = if player.centre_pos.x > self.centre_pos.x { 1.0 } else { -1.0 };
-		self.vel.x += movement_x * self.move_speed();
+	pub fn stitch(&mut self, area: f64, time: f64) {
+		self.stitched_until = time + STITCH_TICKS;
+		self.stitched_area = self.stitched_area.max(area);
+	}
+	
+	pub fn do_movement(&mut self, index: usize, player: &Player, params: &EnemyParams, nav: &NavGraph, platforms: &[Platform], enemies: &[Enemy], broadphase: &Broadphase, time: f64) { //index is where we live in enemies, so we don't collide with ourself
+		if self.stitched() && time >= self.stitched_until {
+			self.stitched_area = 0.0;
+		}
+		let flying = self.kind == EnemyKind::Flyer && !self.stitched(); //stitched flyers drop like anything else
+		if flying {
+			let movement = self.fly(player, params, time);
+			self.vel += movement * params.fly_speed;
+		} else {
+			let movement_x = if self.stitched() { 0.0 } else { self.think(player, params, nav, platforms, broadphase, time) };
+			self.vel.x += movement_x * self.move_speed(time);
+		}
+		let contacts = sweep_all(self, vec2(self.vel.x, 0.0), broadphase.nearby(platforms, enemies, self, vec2(self.vel.x, 0.0), Some(index)).into_iter());
 		self.centre_pos.x += self.vel.x;
-		for object in enemies.iter().map(|x| x as &dyn BoundingBox).chain(platforms.iter().map(|x| x as &dyn BoundingBox)) {
-			let p = self.collision_point_x(object.vel());
-			if let Some(correction) = object.point_collides(p, self.vel()) {
-				let diff = (self.vel() - object.vel()).x;
-				self.centre_pos.x -= diff.signum() * correction.x;
-				self.vel.y -= self.vel.y.signum() * (diff.abs() * FRICTION).min(self.vel.y.abs());
-				self.vel.x = object.vel().x;
+		if let Some(c) = contacts.first() {
+			self.centre_pos.x = c.surface + c.sweep.normal.x * self.size().x / 2.0;
+			let diff = (self.vel() - c.vel).x;
+			self.vel.y -= self.vel.y.signum() * (diff.abs() * FRICTION).min(self.vel.y.abs());
+			self.vel.x = c.vel.x;
+			if !c.is_enemy && !self.stitched() && !flying {
+				self.blocked(params, time);
 			}
 		}
-		self.vel.y -= GRAVITY;
+		if !flying {
+			self.vel.y -= GRAVITY;
+		}
+		let contacts = sweep_all(self, vec2(0.0, self.vel.y), broadphase.nearby(platforms, enemies, self, vec2(0.0, self.vel.y), Some(index)).into_iter());
 		self.centre_pos.y += self.vel.y;
-		for object in enemies.iter().map(|x| x as &dyn BoundingBox).chain(platforms.iter().map(|x| x as &dyn BoundingBox)) {
-			let p = self.collision_point_y(object.vel());
-			if let Some(correction) = object.point_collides(p, self.vel()) {
-				if (self.vel - object.vel()).y.is_sign_negative() {
-					self.grounded = time();
-				}
-				let diff = (self.vel() - object.vel()).y;
-				self.centre_pos.y -= diff.signum() * correction.y;
+		if let Some(c) = contacts.first() {
+			self.centre_pos.y = c.surface + c.sweep.normal.y * self.size().y / 2.0;
+			if c.steep() {
+				self.vel = c.slide(self.vel);
+			} else {
+				let diff = (self.vel() - c.vel).y;
 				self.vel.x -= self.vel.x.signum() * (diff.abs() * FRICTION).min(self.vel.x.abs());
-				self.vel.y = object.vel().y;
+				self.vel.y = c.vel.y;
 			}
 		}
-		self.vel /= self.vel.abs() * DRAG + 1.0;
+		if contacts.iter().any(|c| c.walkable()) {
+			self.grounded = time;
+		}
+		let snap = if time - self.grounded < 1.1 && self.vel.y <= 0.0 { self.vel.x.abs() * 2.0 } else { 0.0 };
+		if let Some(lift) = stand_on_slopes(self, snap, broadphase.nearby(platforms, enemies, self, vec2(0.0, -snap), Some(index)).into_iter()) {
+			self.centre_pos.y += lift;
+			self.vel.y = self.vel.y.max(0.0);
+			self.grounded = time;
+		}
+		let (correction, _) = depenetrate(self, broadphase.nearby(platforms, enemies, self, Vec2::zero(), Some(index)).into_iter());
+		self.push_out(correction, time);
+		let drag = if flying { vec2(DRAG.x, DRAG.x) } else { DRAG }; //no gravity to hold flyers back vertically
+		self.vel /= self.vel.abs() * drag + 1.0;
 	}
 	
-	pub fn render(&self, v: &mut Vec<Vertex>) {
+	pub fn render(&self, v: &mut Vec<Vertex>, alpha: f64) {
 		let t = self.start_time;
 		let shine_rate = 0.03 * (7.0 + 3.0 * RandGen::new((self.start_time * 100.0) as u64).skip(100).f64()) / 10.0;
 		let r = 0.2 / shine_rate;
@@ -370,9 +1287,61 @@ impl Enemy {
 			}
 		}
 		let start_time = x;
-		let mut color = [0.3,0.1,0.3,1.0];
-		color.iter_mut().enumerate().for_each(|(i, x)| if i != 3 { *x *= (self.hp/Self::max_hp()) as f32; });
-		quad(v, self.pos(), 1, self.size(), [color; 4], [[1.0,0.3,1.0,0.5]; 4], start_time, shine_rate, Default::default());
+		let mut color = match self.kind {
+			EnemyKind::Walker => [0.3,0.1,0.3,1.0],
+			EnemyKind::Flyer => [0.1,0.2,0.35,1.0],
+			EnemyKind::Shooter => [0.35,0.15,0.05,1.0],
+		};
+		color.iter_mut().enumerate().for_each(|(i, x)| if i != 3 { *x *= (self.hp/self.max_hp()) as f32; });
+		let pos = lerp(self.prev_pos, self.centre_pos, alpha);
+		quad(v, pos - self.size() / 2.0, 1, self.size(), [color; 4], [[1.0,0.3,1.0,0.5]; 4], start_time, shine_rate, Default::default());
+		if self.stitched() { //criss-cross of thread over the body, shining like thread does
+			let h = self.size() / 2.0;
+			for &(a, b) in [(vec2(-h.x, -h.y), vec2(h.x, h.y)), (vec2(-h.x, h.y), vec2(h.x, -h.y))].iter() {
+				let dir = (b - a).normalize();
+				let l = vec2(-dir.y, dir.x) * 0.008;
+				polygon(v, &[pos + a - l, pos + b - l, pos + b + l, pos + a + l], 2, [0.7,0.7,0.7,1.0], [0.6,0.7,1.0,0.7], [0.0,0.25,0.5], 0.025, [0.1,0.1,0.1]);
+			}
+		}
+	}
+}
+
+#[derive(Debug,Default,Copy,Clone)]
+pub struct Projectile {
+	pub centre_pos: Vec2<f64>,
+	pub prev_pos: Vec2<f64>,
+	pub vel: Vec2<f64>,
+	pub damage: f64,
+	pub expires: f64,
+}
+
+impl BoundingBox for Projectile {
+	fn pos(&self) -> Vec2<f64> { self.centre_pos - self.size() / 2.0 }
+	fn size(&self) -> Vec2<f64> { vec2(0.03,0.03) }
+	fn vel(&self) -> Vec2<f64> { self.vel }
+}
+
+impl Projectile {
+	pub fn new(pos: Vec2<f64>, vel: Vec2<f64>, damage: f64, time: f64) -> Self {
+		Self {
+			centre_pos: pos,
+			prev_pos: pos,
+			vel,
+			damage,
+			expires: time + PROJECTILE_TICKS,
+		}
+	}
+	
+	pub fn update(&mut self, platforms: &[Platform], broadphase: &Broadphase) -> bool { //moves, returns whether it hit a platform, one way platforms let it through
+		self.prev_pos = self.centre_pos;
+		let hit = !sweep_all(self, self.vel, broadphase.nearby(platforms, &[], self, self.vel, None).into_iter().filter(|x| !x.1.one_way())).is_empty();
+		self.centre_pos += self.vel;
+		hit
+	}
+	
+	pub fn render(&self, v: &mut Vec<Vertex>, alpha: f64) {
+		let pos = lerp(self.prev_pos, self.centre_pos, alpha);
+		quad(v, pos - self.size() / 2.0, 3, self.size(), [[0.9,0.4,0.1,1.0]; 4], [[1.0,0.8,0.4,0.8]; 4], [[0.0; 3]; 4], 0.1, Default::default());
 	}
 }
 
@@ -383,11 +1352,42 @@ pub struct Thread {
 	pub time: f64,
 	pub delta: f64,
 	pub detonated: Option<f64>,
+	pub prev_from: Vec2<f64>, //where from was last tick, verlet keeps velocity as the difference
+	pub length: f64, //rest length, how long the segment was when it was laid down
+}
+
+#[derive(Debug,Copy,Clone)]
+pub struct Explosion { //what happens around each thread segment when it goes off
+	pub radius: f64, //anything further than this from the segment doesn't feel it
+	pub damage: f64, //scaled by binomial(3), so up to 3 times this right next to the segment
+	pub falloff: f64, //how fast damage and knockback drop off with distance, 1 is linear
+	pub knockback: f64, //velocity added to enemies, pointing away from the segment
+	pub self_damage: f64, //fraction of the damage we take if we're caught in it, 0 to turn it off
+	pub stitch_bonus: f64, //extra damage to stitched enemies per unit of area their loop enclosed
+}
+
+impl Default for Explosion {
+	fn default() -> Self {
+		Self {
+			radius: 0.08,
+			damage: 10.0,
+			falloff: 1.0,
+			knockback: 0.03,
+			self_damage: 0.0,
+			stitch_bonus: 200.0,
+		}
+	}
+}
+
+impl Explosion {
+	pub fn strength(&self, distance: f64) -> f64 { //1 touching the segment down to 0 at radius
+		if distance >= self.radius { 0.0 } else { (1.0 - distance / self.radius).powf(self.falloff) }
+	}
 }
 
 impl<T: BoundingBox> Collider<T> for Thread {
 	fn collides(&self, other: &T) -> bool {
-		other.point_collides(self.from, Vec2::zero()).is_some() || other.point_collides(self.to, Vec2::zero()).is_some()
+		segment_aabb_distance(self.from, self.to, other.pos(), other.size()) <= self.width() //the thread is a capsule, width is its radius
 	}
 }
 
@@ -399,6 +1399,8 @@ impl Thread {
 			time: start_time,
 			delta: start_time - last_time,
 			detonated: None,
+			prev_from: from,
+			length: (to - from).magnitude(),
 		}
 	}
 	
@@ -407,10 +1409,12 @@ impl Thread {
 	pub fn fall_rate(&self, delta: f64, player_pos: Vec2<f64>) -> f64 {
 		0.0001 * delta * (1.0 + (self.from.y - player_pos.y).max(0.0) * 2.0)
 	}
-	pub fn stretch_rate(&self) -> f64 { 0.00 }
+	pub fn gravity(&self) -> f64 { GRAVITY * 0.25 }
+	pub fn damping(&self) -> f64 { 0.97 }
+	pub fn friction(&self) -> f64 { 0.5 } //fraction of sliding taken away each tick while touching a platform
 	
-	pub fn detonate(&mut self, i: usize) {
-		self.detonated = Some(time() + 120.0 - i as f64 / 6.0);
+	pub fn detonate(&mut self, i: usize, time: f64) {
+		self.detonated = Some(time + 120.0 - i as f64 / 6.0);
 	}
 	
 	pub fn render(&self, v: &mut Vec<Vertex>) {
@@ -436,11 +1440,63 @@ impl Thread {
 		v.extend_from_slice(&quadify(r));
 	}
 	
-	pub fn do_movement(&mut self, delta: f64, parent: Self, child: Self, player_pos: Vec2<f64>) {
-		let x = self.fall_rate(delta, player_pos);
-		self.from.y -= x;
-		self.to.y -= x;
-		self.from += (parent.from - self.from) * self.stretch_rate() * delta;
-		self.to += (child.to - self.to) * self.stretch_rate() * delta;
+	pub fn simulate(thread: &mut [Thread], anchor: Vec2<f64>, platforms: &[Platform], broadphase: &Broadphase, delta: f64) { //verlet rope, thread[0].to is pinned to anchor and every other segment's to is the next newest one's from
+		for t in thread.iter_mut() {
+			let vel = (t.from - t.prev_from) * t.damping().powf(delta);
+			t.prev_from = t.from;
+			t.from += vel;
+			t.from.y -= t.gravity() * delta * delta;
+		}
+		let nearby = thread.iter().map(|t| { //platforms each node could end up touching this tick, so the iterations don't have to keep asking
+			let r = t.width() + t.length + (t.from - t.prev_from).magnitude();
+			broadphase.platforms.query(t.from - vec2(r, r), vec2(r, r) * 2.0)
+		}).collect::<Vec<_>>();
+		let mut contacts = vec![None; thread.len()]; //last surface each node got pushed out of, and how fast that surface is going
+		for _ in 0..ROPE_ITERATIONS {
+			for i in 0..thread.len() {
+				let a = if i == 0 { anchor } else { thread[i-1].from };
+				let d = thread[i].from - a;
+				let l = d.magnitude();
+				if l < EPSILON {
+					continue;
+				}
+				let error = d * ((l - thread[i].length) / l);
+				if i == 0 { //the anchor doesn't move, so this end takes all of it
+					thread[i].from -= error;
+				} else {
+					thread[i-1].from += error * 0.5;
+					thread[i].from -= error * 0.5;
+				}
+			}
+			for (i, t) in thread.iter_mut().enumerate() { //collide after the constraints so nodes end up outside platforms, bending the thread around corners
+				for &j in nearby[i].iter().filter(|&&j| j < platforms.len()) {
+					if let Some((correction, vel)) = t.push_out_of(&platforms[j]) {
+						t.from += correction;
+						contacts[i] = Some((correction.normalize(), vel));
+					}
+				}
+			}
+		}
+		for (t, c) in thread.iter_mut().zip(contacts.into_iter()) {
+			if let Some((n, surface_vel)) = c { //friction, take away some of the sliding along the surface and all of the going into it
+				let v = t.from - t.prev_from - surface_vel;
+				let into = dot(v, n).min(0.0);
+				let along = v - n * dot(v, n);
+				t.prev_from += n * into + along * t.friction();
+			}
+		}
+		for i in 0..thread.len() {
+			thread[i].to = if i == 0 { anchor } else { thread[i-1].from };
+		}
+	}
+	
+	fn push_out_of(&self, platform: &Platform) -> Option<(Vec2<f64>, Vec2<f64>)> { //correction that moves the from node out of platform, and the platform's velocity
+		let w = self.width();
+		let node = AABB { pos: self.from - vec2(w, w), size: vec2(w, w) * 2.0, vel: Vec2::zero() };
+		let correction = node.penetration(platform)?;
+		if platform.one_way() && !(correction.y > 0.0 && self.prev_from.y - w >= platform.pos().y + platform.size().y - platform.vel().y - RIDING_TOLERANCE) {
+			return None; //one way platforms only catch thread that was already above them
+		}
+		Some((correction, platform.vel()))
 	}
 }
@@ -0,0 +1,264 @@
+use crate::prelude::*;
+use std::fmt;
+
+//levels are plain text, one thing per line, numbers are in world units and times are in ticks, # starts a comment
+//	start x y                          where the player starts and respawns
+//	platform x y w h [one_way]         centre and size of a box
+//	polygon x y x y x y ...            convex outline going anticlockwise, at least 3 points
+//	path loop|ping_pong [phase]        makes the last platform/polygon move, followed by its waypoints
+//	waypoint x y duration [easing]     where the platform's centre goes and how long it takes to get to the next one, easing is smooth or linear (linear if left out)
+//	spawner x y interval cap [kind]    spawns a walker, flyer or shooter (walker if left out) at x y every interval ticks, if less than cap of its enemies are alive
+//	waves ticks speedup toughness      how the wave director escalates, see WaveParams, at most once, without it nothing escalates
+//	hazard x y w h damage              box that hurts the player by damage every tick they're in it
+//	goal x y w h                       where the level ends, at most one
+pub const FIRST_LEVEL: &str = include_str!("levels/first.level");
+
+#[derive(Debug,Clone,PartialEq)]
+pub struct LevelError {
+	pub line: usize,
+	pub column: usize,
+	pub message: String,
+}
+
+impl fmt::Display for LevelError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+	}
+}
+
+#[derive(Debug,Copy,Clone)]
+struct Token<'a> {
+	text: &'a str,
+	column: usize,
+}
+
+struct Line<'a> {
+	tokens: Vec<Token<'a>>,
+	next: usize,
+	line: usize,
+	end: usize, //column just past the last character, for complaining about things that are missing
+}
+
+impl<'a> Line<'a> {
+	fn new(src: &'a str, line: usize) -> Self {
+		let src = src.split('#').next().unwrap();
+		let mut tokens = Vec::new();
+		let mut start = None;
+		for (i, c) in src.char_indices().chain(std::iter::once((src.len(), ' '))) {
+			match (start, c.is_whitespace()) {
+				(None, false) => start = Some(i),
+				(Some(s), true) => {
+					tokens.push(Token { text: &src[s..i], column: src[..s].chars().count() + 1 });
+					start = None;
+				},
+				_ => {},
+			}
+		}
+		Self {
+			tokens,
+			next: 0,
+			line,
+			end: src.trim_end().chars().count() + 1,
+		}
+	}
+	
+	fn error(&self, column: usize, message: String) -> LevelError {
+		LevelError { line: self.line, column, message }
+	}
+	
+	fn word(&mut self) -> Option<Token<'a>> {
+		let r = self.tokens.get(self.next).cloned();
+		self.next += 1;
+		r
+	}
+	
+	fn number(&mut self, what: &str) -> Result<f64, LevelError> {
+		match self.word() {
+			Some(t) => t.text.parse::<f64>().ok().filter(|x| x.is_finite()).ok_or_else(|| self.error(t.column, format!("expected a number for {}, found \"{}\"", what, t.text))),
+			None => Err(self.error(self.end, format!("missing {}", what))),
+		}
+	}
+	
+	fn vec2(&mut self, what: &str) -> Result<Vec2<f64>, LevelError> {
+		Ok(vec2(self.number(&format!("{} x", what))?, self.number(&format!("{} y", what))?))
+	}
+	
+	fn positive(&mut self, what: &str) -> Result<f64, LevelError> {
+		let column = self.tokens.get(self.next).map(|x| x.column).unwrap_or(self.end);
+		let x = self.number(what)?;
+		if x > 0.0 { Ok(x) } else { Err(self.error(column, format!("{} has to be more than 0", what))) }
+	}
+	
+	fn finish(&self) -> Result<(), LevelError> {
+		match self.tokens.get(self.next) {
+			Some(t) => Err(self.error(t.column, format!("unexpected \"{}\"", t.text))),
+			None => Ok(()),
+		}
+	}
+}
+
+fn close_path(r: &GameState, path_line: Option<(usize, usize)>) -> Result<(), LevelError> { //checks the path that was being built, if there was one
+	if let Some((line, column)) = path_line {
+		let n = r.platforms.last().and_then(|x| x.path.as_ref()).map(|x| x.waypoints.len()).unwrap_or(0);
+		if n < 2 {
+			return Err(LevelError { line, column, message: format!("path needs at least 2 waypoints, found {}", n) });
+		}
+	}
+	Ok(())
+}
+
+fn convex_anticlockwise(points: &[Vec2<f64>]) -> bool {
+	let n = points.len();
+	(0..n).all(|i| {
+		let (a, b, c) = (points[i], points[(i + 1) % n], points[(i + 2) % n]);
+		let (e, f) = (b - a, c - b);
+		e.x * f.y - e.y * f.x > 0.0
+	})
+}
+
+pub fn parse_level(src: &str) -> Result<GameState, LevelError> {
+	let mut r = GameState::new();
+	let mut start = None;
+	let mut goal_line = None;
+	let mut waves_line = None;
+	let mut path_line = None; //where the path we're adding waypoints to was started, for complaining about it
+	for (i, line) in src.lines().enumerate() {
+		let mut l = Line::new(line, i + 1);
+		let keyword = match l.word() {
+			Some(x) => x,
+			None => continue,
+		};
+		if keyword.text != "waypoint" {
+			close_path(&r, path_line.take())?;
+		}
+		match keyword.text {
+			"start" => {
+				if start.is_some() {
+					return Err(l.error(keyword.column, format!("start was already set on line {}", start.unwrap())));
+				}
+				r.player_start = l.vec2("start")?;
+				start = Some(l.line);
+			},
+			"platform" => {
+				let centre_pos = l.vec2("platform centre")?;
+				let size = vec2(l.positive("platform width")?, l.positive("platform height")?);
+				let collision = match l.word() {
+					Some(t) if t.text == "one_way" => Collision::OneWay,
+					Some(t) => return Err(l.error(t.column, format!("expected one_way or nothing, found \"{}\"", t.text))),
+					None => Collision::Solid,
+				};
+				r.platforms.push(Platform { centre_pos, size, collision, .. Default::default() });
+			},
+			"polygon" => {
+				let mut points = Vec::new();
+				while l.tokens.get(l.next).is_some() {
+					points.push(l.vec2(&format!("polygon point {}", points.len() + 1))?);
+				}
+				if points.len() < 3 {
+					return Err(l.error(keyword.column, format!("polygon needs at least 3 points, found {}", points.len())));
+				}
+				if !convex_anticlockwise(&points) {
+					return Err(l.error(keyword.column, "polygon has to be convex with its points going anticlockwise".to_string()));
+				}
+				r.platforms.push(Platform::from_polygon(points));
+			},
+			"path" => {
+				let mode = match l.word() {
+					Some(t) if t.text == "loop" => PathMode::Loop,
+					Some(t) if t.text == "ping_pong" => PathMode::PingPong,
+					Some(t) => return Err(l.error(t.column, format!("expected loop or ping_pong, found \"{}\"", t.text))),
+					None => return Err(l.error(l.end, "missing path mode, loop or ping_pong".to_string())),
+				};
+				let phase = if l.tokens.get(l.next).is_some() { l.number("path phase")? } else { 0.0 };
+				match r.platforms.last_mut() {
+					Some(p) if p.path.is_none() => p.path = Some(PlatformPath { phase, .. PlatformPath::new(Vec::new(), mode) }),
+					Some(_) => return Err(l.error(keyword.column, "the last platform already has a path".to_string())),
+					None => return Err(l.error(keyword.column, "path has to come after the platform it moves".to_string())),
+				}
+				path_line = Some((l.line, keyword.column));
+			},
+			"waypoint" => {
+				let pos = l.vec2("waypoint")?;
+				let duration = l.positive("waypoint duration")?;
+				let easing = match l.word() {
+					Some(t) if t.text == "smooth" => Easing::Smooth,
+					Some(t) if t.text == "linear" => Easing::Linear,
+					Some(t) => return Err(l.error(t.column, format!("expected smooth, linear or nothing, found \"{}\"", t.text))),
+					None => Easing::Linear,
+				};
+				match (path_line, r.platforms.last_mut().and_then(|x| x.path.as_mut())) {
+					(Some(_), Some(path)) => path.waypoints.push(Waypoint { pos, duration, easing }),
+					_ => return Err(l.error(keyword.column, "waypoint has to come after a path".to_string())),
+				}
+			},
+			"spawner" => {
+				let pos = l.vec2("spawner")?;
+				let interval = l.positive("spawner interval")?;
+				let column = l.tokens.get(l.next).map(|x| x.column).unwrap_or(l.end);
+				let cap = l.number("spawner cap")?;
+				if cap < 1.0 || cap.fract() != 0.0 {
+					return Err(l.error(column, "spawner cap has to be a whole number, at least 1".to_string()));
+				}
+				let kind = match l.word() {
+					Some(t) if t.text == "walker" => EnemyKind::Walker,
+					Some(t) if t.text == "flyer" => EnemyKind::Flyer,
+					Some(t) if t.text == "shooter" => EnemyKind::Shooter,
+					Some(t) => return Err(l.error(t.column, format!("expected walker, flyer, shooter or nothing, found \"{}\"", t.text))),
+					None => EnemyKind::Walker,
+				};
+				r.spawners.push(Spawner::new(pos, kind, interval, cap as usize));
+			},
+			"waves" => {
+				if let Some(line) = waves_line {
+					return Err(l.error(keyword.column, format!("waves were already set on line {}", line)));
+				}
+				let wave_ticks = l.positive("wave length")?;
+				let column = l.tokens.get(l.next).map(|x| x.column).unwrap_or(l.end);
+				let interval_scale = l.positive("wave speedup")?;
+				if interval_scale > 1.0 {
+					return Err(l.error(column, "wave speedup can't be more than 1, that would slow spawning down".to_string()));
+				}
+				let column = l.tokens.get(l.next).map(|x| x.column).unwrap_or(l.end);
+				let toughness = l.number("wave toughness")?;
+				if toughness < 0.0 {
+					return Err(l.error(column, "wave toughness can't be less than 0, enemies would end up with no hp".to_string()));
+				}
+				r.waves.params = WaveParams::escalating(wave_ticks, interval_scale, toughness);
+				waves_line = Some(l.line);
+			},
+			"hazard" => {
+				let centre_pos = l.vec2("hazard centre")?;
+				let size = vec2(l.positive("hazard width")?, l.positive("hazard height")?);
+				let column = l.tokens.get(l.next).map(|x| x.column).unwrap_or(l.end);
+				let damage = l.number("hazard damage")?;
+				if damage < 0.0 {
+					return Err(l.error(column, "hazard damage can't be less than 0, that would heal".to_string()));
+				}
+				r.hazards.push(Hazard { centre_pos, size, damage });
+			},
+			"goal" => {
+				if let Some(line) = goal_line {
+					return Err(l.error(keyword.column, format!("goal was already set on line {}", line)));
+				}
+				let centre_pos = l.vec2("goal centre")?;
+				let size = vec2(l.positive("goal width")?, l.positive("goal height")?);
+				r.goal = Some(Goal { centre_pos, size });
+				goal_line = Some(l.line);
+			},
+			_ => return Err(l.error(keyword.column, format!("unknown keyword \"{}\"", keyword.text))),
+		}
+		l.finish()?;
+	}
+	close_path(&r, path_line)?;
+	if start.is_none() {
+		return Err(LevelError { line: src.lines().count().max(1), column: 1, message: "level has no start".to_string() });
+	}
+	r.reset_player();
+	for p in r.platforms.iter_mut() {
+		if let Some(path) = &p.path {
+			p.centre_pos = path.pos_at(0.0); //otherwise it would jump onto its path on the first tick
+		}
+	}
+	r.build_nav();
+	Ok(r)
+}
@@ -1,59 +1,97 @@
 #![feature(decl_macro,const_float_bits_conv)]
-mod prelude; use prelude::*;
+pub mod prelude; use prelude::*;
+#[cfg(feature = "web")]
 mod boiler_plate;
-mod vertex;
-mod game_state;
+pub mod vertex;
+pub mod game_state;
+pub mod timestep;
+pub mod broadphase;
+pub mod path;
+pub mod level;
+pub mod replay;
+pub mod save;
+pub mod netcode;
+pub mod desync;
+pub mod ai;
+pub mod nav;
+pub mod reach;
+pub mod waves;
 
+#[cfg(feature = "web")]
 #[wasm_bindgen(start)]
 pub fn main() -> Result<(), JsValue> {
 	initial_setup();
 	setup_input_events();
 	let (gl, u_loc) = setup_rendering().expect(l!());
 	
-	let mut game_state = GameState::new();
-	game_state.platforms.push(Platform {
-		centre_pos: vec2(0.0,-0.8),
-		size: vec2(200.0,0.4),
-	});
-	game_state.platforms.push(Platform {
-		centre_pos: vec2(-1.0,0.0),
-		size: vec2(0.3,1.0),
-	});
-	game_state.platforms.push(Platform {
-		centre_pos: vec2(1.0,0.0),
-		size: vec2(0.3,1.0),
-	});
-	game_state.platforms.push(Platform {
-		centre_pos: vec2(-0.5,-0.2),
-		size: vec2(0.4,0.1),
-	});
-	game_state.platforms.push(Platform {
-		centre_pos: vec2(0.0,0.2),
-		size: vec2(0.4,0.1),
-	});
-	game_state.platforms.push(Platform {
-		centre_pos: vec2(0.5,0.6),
-		size: vec2(0.4,0.1),
-	});
+	let mut game_state = parse_level(FIRST_LEVEL).map_err(|e| e.to_string())?;
+	let delta = 1.0; //the sim is tuned in 60hz ticks, real time is handled by the timestep
+	let mut recorder = Recorder::new(&mut game_state, CryptoRng.next(), delta);
+	let relay = relay_url().map(|x| connect_relay(&x)).transpose()?;
+	let mut rollback: Option<Rollback> = None; //set once the relay pairs us with someone
+	let mut desync_reported = false;
 	
+	let mut timestep = FixedStep::new(1000.0 / 60.0, 10);
 	let f = None.rc(); let g = f.clone();
 	*f.borrow_mut() = Some(Closure::wrap(Box::new(move|| {
-		let delta = 1.0;
-		uniforms().add_time(delta);
 		//run_callbacks();
-		if time() % 100.0 < 0.1 {
-			if game_state.enemies.len() < 3 {
-				game_state.enemies.push(Enemy {
-					centre_pos: vec2(0.7,0.8),
-					.. Enemy::new()
-				})
+		let messages = net_messages().drain(..).collect::<Vec<_>>();
+		for msg in messages.iter() {
+			let mut parts = msg.split(' ');
+			match (parts.next(), parts.next().and_then(|x| x.parse::<usize>().ok()), parts.next().and_then(|x| x.parse::<u64>().ok())) {
+				(Some("hello"), Some(local), Some(seed)) => { //relay paired us up, both sides start the same fresh game
+					game_state = parse_level(FIRST_LEVEL).expect(l!());
+					game_state.add_coop_player();
+					game_state.seed(seed);
+					game_state.follow = local;
+					rollback = Some(Rollback::new(local, delta));
+					desync_reported = false;
+				},
+				_ => if let Some(r) = &mut rollback {
+					if let Err(e) = r.receive(&mut game_state, msg) {
+						log!("bad message from relay: {}", e);
+					}
+					if let (Some(t), false) = (r.desync, desync_reported) { //both players' dumps can go through diff_saves to find what went wrong
+						log!("desynced at tick {}, our state at tick {}: {}", t, r.tick, to_hex(&save_game(&game_state)));
+						desync_reported = true;
+					}
+				},
 			}
 		}
-		game_state.tick(delta);
-		for event in input_events().drain(..) {
-			game_state.input_event(event);
+		for _ in 0..timestep.advance(now()) {
+			if rollback.as_ref().map(|x| !x.can_advance()).unwrap_or(false) {
+				break; //too far ahead of the other player, wait for them to catch up
+			}
+			let events = input_events().drain(..).collect::<Vec<_>>();
+			match (&mut rollback, &relay) {
+				(Some(r), Some(ws)) => { //saving and loading would desync us, so they're single player only
+					let msg = r.advance(&mut game_state, events);
+					if let Err(e) = ws.send_with_str(&msg) {
+						log!("couldn't send to relay: {:?}", e);
+					}
+				},
+				_ => {
+					for e in events.iter() {
+						match e {
+							InputEvent::KeyDown(k) if k == "F8" => log!("{}", recorder.recording), //dump the replay so testers can send it to us
+							InputEvent::KeyDown(k) if k == "k" => if let Err(e) = save_to_slot(0, &game_state) {
+								log!("couldn't save: {}", e);
+							},
+							InputEvent::KeyDown(k) if k == "n" => game_state.show_nav = !game_state.show_nav,
+							InputEvent::KeyDown(k) if k == "l" => match load_from_slot(0) {
+								Ok(x) => game_state = x,
+								Err(e) => log!("couldn't load: {}", e),
+							},
+							_ => {},
+						}
+					}
+					recorder.tick(&mut game_state, events);
+				},
+			}
 		}
-		render(&gl, &u_loc, &game_state.render());
+		let alpha = timestep.alpha();
+		uniforms().set_time(game_state.render_time(alpha)).set_cam_pos(game_state.render_cam_pos(alpha));
+		render(&gl, &u_loc, &game_state.render(alpha));
 		request_animation_frame(g.borrow().as_ref().unwrap());
 	}) as Box<dyn FnMut()>));
 	request_animation_frame(f.borrow().as_ref().unwrap());
@@ -0,0 +1,302 @@
+use crate::prelude::*;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+//navigation graph over the tops of platforms, built once per level
+//links come from actually stepping the movement code's numbers forward, so if the graph says something's reachable the mover can really get there
+//jump and drop links are only kept if some arc along them misses everything solid, moving platforms and bridges aren't counted as in the way
+//polygons count as their bounding box for where their surface is
+const STEP_HEIGHT: f64 = 0.02; //surfaces this close in height that touch can be walked between
+const MAX_FALL: f64 = 3.0; //don't bother following arcs further down than this
+const JUMP_COST: f64 = 0.3; //extra cost for jumping, so walking is preferred when it's about as far
+const TAKE_OFF_SPEEDS: [f64; 5] = [0.0, 0.25, 0.5, 0.75, 1.0]; //fractions of top speed tried when looking for an arc that misses everything, slower ones go up steeper
+
+#[derive(Debug,Copy,Clone)]
+pub struct Envelope { //how something moves, everything the graph needs to know to work out what it can reach
+	pub size: Vec2<f64>,
+	pub ground_speed: f64, //acceleration per tick while grounded
+	pub air_speed: f64, //acceleration per tick in the air
+	pub jump_vel: f64,
+	pub coyote: f64, //ticks after walking off an edge that it can still jump
+	pub drop_through: bool, //can fall through one way platforms
+}
+
+impl Envelope {
+	pub fn enemy(params: &EnemyParams, kind: EnemyKind) -> Self { //walkers and shooters share their movement numbers but not their size
+		Self {
+			size: Enemy::new(kind, 0.0).size(),
+			ground_speed: Enemy::ground_speed(),
+			air_speed: Enemy::air_speed(),
+			jump_vel: params.jump_vel,
+			coyote: 1.1,
+			drop_through: false,
+		}
+	}
+	
+	pub fn player(player: &Player) -> Self {
+		Self {
+			size: player.size(),
+			ground_speed: player.ground_speed(),
+			air_speed: player.air_speed(),
+			jump_vel: player.jump_vel(),
+			coyote: player.grounded_limit(),
+			drop_through: true,
+		}
+	}
+	
+	pub fn top_speed(&self) -> f64 { //what walking settles at once drag catches up
+		let mut v = 0.0;
+		for _ in 0..200 {
+			v += self.ground_speed;
+			v /= v * DRAG.x + 1.0;
+		}
+		v
+	}
+	
+	pub fn arc(&self, jump: bool) -> Vec<Vec2<f64>> { //where the feet go each tick, leaving the ground at top speed going right, same order as do_movement
+		let mut vel = vec2(self.top_speed(), if jump { self.jump_vel } else { 0.0 });
+		let mut pos = Vec2::zero();
+		let mut r = vec![pos];
+		while pos.y > -MAX_FALL && r.len() < 1000 {
+			vel.x += self.air_speed;
+			pos.x += vel.x;
+			vel.y -= GRAVITY;
+			pos.y += vel.y;
+			vel /= vel.abs() * DRAG + 1.0;
+			r.push(pos);
+		}
+		r
+	}
+}
+
+pub struct Tracer<'a> { //follows movement arcs through a level to see whether they hit anything solid on the way
+	pub envelope: Envelope,
+	pub jump: Vec<Vec2<f64>>,
+	pub fall: Vec<Vec2<f64>>,
+	solid: Vec<(usize, &'a Platform)>,
+}
+
+impl<'a> Tracer<'a> {
+	pub fn new(platforms: &'a [Platform], envelope: &Envelope) -> Self {
+		Self {
+			envelope: *envelope,
+			jump: envelope.arc(true),
+			fall: envelope.arc(false),
+			solid: platforms.iter().enumerate().filter(|(_, p)| !p.one_way() && p.path.is_none() && p.bridge.is_none()).collect(),
+		}
+	}
+	
+	fn body(&self, feet: Vec2<f64>) -> AABB {
+		AABB { pos: feet - vec2(self.envelope.size.x / 2.0, 0.0), size: self.envelope.size, vel: Vec2::zero() }
+	}
+	
+	pub fn clear(&self, from: &NavNode, jump: bool, x_range: (f64, f64), lowest: f64, landing: Option<usize>, mut done: impl FnMut(Vec2<f64>, bool) -> Option<bool>) -> bool { //whether any arc off from gets somewhere without going through anything solid
+		//arcs start all along from (or off the end of it when falling) going either way at a few speeds, and are given to done each tick with whether they're falling
+		//done says whether the arc got where it was going or gave up, or None to carry on, landing is a platform that's only in the way from underneath
+		//x_range and lowest are the sideways extent and the bottom of where it's going, arcs that can't get near it aren't tried
+		let arc = if jump { &self.jump } else { &self.fall };
+		let half = self.envelope.size.x / 2.0;
+		let coyote = self.envelope.coyote * self.envelope.top_speed();
+		let mut reach = 0.0f64; //furthest sideways an arc gets before it's below lowest
+		for t in 1..arc.len() {
+			reach = reach.max(arc[t].x);
+			if arc[t].y < arc[t-1].y && from.y + arc[t].y < lowest {
+				break;
+			}
+		}
+		let (x0, x1) = ((from.left - coyote - half).max(x_range.0 - reach) - reach - half, (from.right + coyote + half).min(x_range.1 + reach) + reach + half);
+		let solid = self.solid.iter().filter(|(_, p)| p.pos().x < x1 && p.pos().x + p.size().x > x0).copied().collect::<Vec<_>>(); //only what's between here and there
+		for &dir in [1.0, -1.0].iter() {
+			let (lo, hi) = match (jump, dir > 0.0) {
+				(true, true) => (from.left, from.right + coyote),
+				(true, false) => (from.left - coyote, from.right),
+				(false, true) => (from.right + half, from.right + half + coyote),
+				(false, false) => (from.left - half - coyote, from.left - half),
+			};
+			let (lo, hi) = (lo.max(x_range.0 - reach), hi.min(x_range.1 + reach));
+			if lo > hi {
+				continue;
+			}
+			let n = ((hi - lo) / half).ceil().max(1.0) as usize;
+			for k in 0..=n {
+				let start = vec2(lo + (hi - lo) * k as f64 / n as f64, from.y);
+				if solid.iter().any(|(_, p)| self.body(start).penetration(*p).is_some()) {
+					continue; //can't stand there
+				}
+				for &speed in TAKE_OFF_SPEEDS.iter() {
+					if self.trace(arc, start, dir * speed, &solid, landing, &mut done) {
+						return true;
+					}
+				}
+			}
+		}
+		false
+	}
+	
+	fn trace(&self, arc: &[Vec2<f64>], start: Vec2<f64>, scale: f64, solid: &[(usize, &Platform)], landing: Option<usize>, done: &mut impl FnMut(Vec2<f64>, bool) -> Option<bool>) -> bool {
+		for t in 1..arc.len() {
+			let feet = start + vec2(arc[t].x * scale, arc[t].y);
+			if let Some(r) = done(feet, arc[t].y < arc[t-1].y) {
+				return r;
+			}
+			let body = self.body(feet);
+			if solid.iter().any(|&(i, p)| body.penetration(p).is_some() && (Some(i) != landing || feet.y < p.pos().y)) {
+				return false;
+			}
+		}
+		false
+	}
+	
+	pub fn lands(&self, from: &NavNode, on: &NavNode, jump: bool) -> bool { //whether some arc off from comes down on on
+		let half = self.envelope.size.x / 2.0;
+		let (left, right) = (on.left - half, on.right + half);
+		self.clear(from, jump, (left, right), on.y, Some(on.platform), |feet, falling| {
+			if falling && feet.y <= on.y { Some(feet.x >= left && feet.x <= right) } else { None }
+		})
+	}
+}
+
+fn reaches(arc: &[Vec2<f64>], gap: f64, dy: f64, head_start: f64) -> bool { //whether an arc gets gap across by the time it comes down through dy
+	(1..arc.len()).any(|t| arc[t].y < arc[t-1].y && arc[t-1].y >= dy && arc[t].y <= dy && arc[t].x + head_start >= gap)
+}
+
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub enum LinkKind {
+	Walk,
+	Jump,
+	Drop, //walk off the edge and fall
+}
+
+#[derive(Debug,Copy,Clone)]
+pub struct NavLink {
+	pub to: usize,
+	pub kind: LinkKind,
+	pub cost: f64,
+}
+
+#[derive(Debug,Copy,Clone)]
+pub struct NavNode { //top of a platform
+	pub platform: usize,
+	pub left: f64,
+	pub right: f64,
+	pub y: f64,
+	pub one_way: bool,
+}
+
+impl NavNode {
+	pub fn centre(&self) -> Vec2<f64> { vec2((self.left + self.right) / 2.0, self.y) }
+	pub fn gap(&self, other: &NavNode) -> f64 { (other.left - self.right).max(self.left - other.right).max(0.0) }
+}
+
+#[derive(Debug,Clone,Default)]
+pub struct NavGraph {
+	pub nodes: Vec<NavNode>,
+	pub links: Vec<Vec<NavLink>>, //links[i] are the ways out of nodes[i]
+}
+
+impl NavGraph {
+	pub fn new(platforms: &[Platform], envelope: &Envelope) -> Self {
+		let nodes = platforms.iter().enumerate()
+			.filter(|(_, p)| p.path.is_none() && p.bridge.is_none()) //anything that moves or goes away can't be planned around
+			.map(|(i, p)| NavNode { platform: i, left: p.pos().x, right: p.pos().x + p.size().x, y: p.pos().y + p.size().y, one_way: p.one_way() })
+			.collect::<Vec<_>>();
+		let tracer = Tracer::new(platforms, envelope);
+		let (jump, fall) = (&tracer.jump, &tracer.fall);
+		let coyote = envelope.coyote * envelope.top_speed();
+		let half = envelope.size.x / 2.0;
+		let mut links = vec![Vec::new(); nodes.len()];
+		for (i, a) in nodes.iter().enumerate() {
+			for (j, b) in nodes.iter().enumerate() {
+				if i == j {
+					continue;
+				}
+				let gap = a.gap(b);
+				let dy = b.y - a.y;
+				let distance = (b.centre() - a.centre()).magnitude();
+				let drop_side = if b.right > a.right + half { Some(b.left - a.right) } else if b.left < a.left - half { Some(a.left - b.right) } else { None };
+				let kind = if dy.abs() < STEP_HEIGHT && gap < STEP_HEIGHT {
+					Some(LinkKind::Walk)
+				} else if dy < 0.0 && drop_side.map(|x| reaches(fall, x.max(0.0), dy, half)).unwrap_or(false) && tracer.lands(a, b, false) {
+					Some(LinkKind::Drop)
+				} else if dy < 0.0 && a.one_way && envelope.drop_through && gap == 0.0 {
+					Some(LinkKind::Drop) //straight down through the floor
+				} else if reaches(jump, gap, dy, coyote + half) && tracer.lands(a, b, true) {
+					Some(LinkKind::Jump)
+				} else {
+					None
+				};
+				if let Some(kind) = kind {
+					let cost = distance + if kind == LinkKind::Jump { JUMP_COST } else { 0.0 };
+					links[i].push(NavLink { to: j, kind, cost });
+				}
+			}
+		}
+		Self { nodes, links }
+	}
+	
+	pub fn surface_below(&self, pos: Vec2<f64>) -> Option<usize> { //highest surface under pos, what something there is standing on or about to land on
+		self.nodes.iter().enumerate()
+			.filter(|(_, n)| n.left <= pos.x && pos.x <= n.right && n.y <= pos.y + STEP_HEIGHT)
+			.fold(None, |best: Option<(usize, f64)>, (i, n)| match best {
+				Some((_, y)) if y >= n.y => best,
+				_ => Some((i, n.y)),
+			})
+			.map(|x| x.0)
+	}
+	
+	pub fn path(&self, from: usize, to: usize) -> Option<Vec<NavLink>> { //a*, returns the links to follow, empty if already there
+		let h = |i: usize| (self.nodes[to].centre() - self.nodes[i].centre()).magnitude();
+		let mut cost = vec![f64::INFINITY; self.nodes.len()];
+		let mut came_from: Vec<Option<(usize, NavLink)>> = vec![None; self.nodes.len()];
+		let mut open = BinaryHeap::new();
+		cost[from] = 0.0;
+		open.push(Reverse((h(from).to_bits(), from))); //non negative floats sort the same as their bits
+		while let Some(Reverse((_, i))) = open.pop() {
+			if i == to {
+				let mut r = Vec::new();
+				let mut i = to;
+				while let Some((prev, link)) = came_from[i] {
+					r.push(link);
+					i = prev;
+				}
+				r.reverse();
+				return Some(r);
+			}
+			for link in self.links[i].iter() {
+				let c = cost[i] + link.cost;
+				if c < cost[link.to] {
+					cost[link.to] = c;
+					came_from[link.to] = Some((i, *link));
+					open.push(Reverse(((c + h(link.to)).to_bits(), link.to)));
+				}
+			}
+		}
+		None
+	}
+	
+	pub fn render(&self, v: &mut Vec<Vertex>) { //debug view, surfaces in white, walk links green, jumps yellow, drops blue
+		for n in self.nodes.iter() {
+			line(v, vec2(n.left, n.y), vec2(n.right, n.y), [1.0,1.0,1.0,0.6]);
+		}
+		for (i, links) in self.links.iter().enumerate() {
+			for l in links.iter() {
+				let color = match l.kind {
+					LinkKind::Walk => [0.2,1.0,0.2,0.6],
+					LinkKind::Jump => [1.0,1.0,0.2,0.6],
+					LinkKind::Drop => [0.3,0.5,1.0,0.6],
+				};
+				let (a, b) = (self.nodes[i].centre(), self.nodes[l.to].centre());
+				line(v, a, lerp(a, b, 0.9), color); //stopping short shows which way it goes
+			}
+		}
+	}
+}
+
+fn line(v: &mut Vec<Vertex>, a: Vec2<f64>, b: Vec2<f64>, color: [f32; 4]) {
+	if (b - a).magnitude() < EPSILON {
+		return;
+	}
+	let dir = (b - a).normalize();
+	let l = vec2(-dir.y, dir.x) * 0.004;
+	polygon(v, &[a - l, b - l, b + l, a + l], 12, color, [0.0; 4], [0.0; 3], 0.0, Default::default());
+}
@@ -0,0 +1,144 @@
+use crate::prelude::*;
+use std::collections::{BTreeMap,VecDeque};
+
+//rollback co-op: both peers run the whole sim, each sends its own inputs every tick
+//we don't wait for the other player's inputs, we guess they did nothing new (held keys stay held) and run ahead
+//when their real inputs turn up for a tick we already ran, we load the snapshot from before it and run forward again
+//message per tick: "tick N" then one event per line, in the same format as recordings
+//every HASH_INTERVAL ticks that are final on our side we also send "hash N hex", so the other side can tell if we've desynced
+pub const MAX_ROLLBACK: u64 = 60; //how far we'll run ahead of the other player before waiting for them
+
+fn snapshot(game_state: &mut GameState) -> GameState { //everything but the nav graph and broadphase, they're big and restore carries them over instead
+	let nav = std::mem::take(&mut game_state.nav);
+	let broadphase = std::mem::take(&mut game_state.broadphase);
+	let r = game_state.clone();
+	game_state.nav = nav;
+	game_state.broadphase = broadphase;
+	r
+}
+
+fn restore(game_state: &mut GameState, snapshot: &GameState) { //the nav graph only depends on the level so it's the same for every tick, and the broadphase gets rebuilt at the start of each one
+	let nav = std::mem::take(&mut game_state.nav);
+	let broadphase = std::mem::take(&mut game_state.broadphase);
+	*game_state = snapshot.clone();
+	game_state.nav = nav;
+	game_state.broadphase = broadphase;
+}
+
+#[derive(Debug)]
+pub struct Rollback {
+	pub local: usize, //which player we are, 0 is game_state.player and 1 is game_state.coop
+	pub tick: u64, //ticks simulated so far
+	pub delta: f64,
+	inputs: [BTreeMap<u64, Vec<InputEvent>>; 2], //events each player sent after each tick
+	remote_confirmed: u64, //we've got the other player's inputs for every tick before this
+	snapshots: VecDeque<(u64, GameState)>, //state from just before each tick that might get rolled back
+	pub rollbacks: usize, //how many times we've had to rewind, for debugging
+	pub hashes: HashStream, //hashes of states that can't be rolled back any more
+	sent_hashes: u64, //hashes before this tick have been sent
+	remote_hashes: BTreeMap<u64, StateHash>, //hashes from the other side we haven't got our own to compare to yet
+	pub desync: Option<u64>, //first tick where the two sides' states didn't match
+}
+
+impl Rollback {
+	pub fn new(local: usize, delta: f64) -> Self {
+		Self {
+			local,
+			tick: 0,
+			delta,
+			inputs: [BTreeMap::new(), BTreeMap::new()],
+			remote_confirmed: 0,
+			snapshots: VecDeque::new(),
+			rollbacks: 0,
+			hashes: HashStream::default(),
+			sent_hashes: 0,
+			remote_hashes: BTreeMap::new(),
+			desync: None,
+		}
+	}
+	
+	pub fn remote(&self) -> usize { 1 - self.local }
+	
+	pub fn can_advance(&self) -> bool {
+		self.tick < self.remote_confirmed + MAX_ROLLBACK
+	}
+	
+	fn simulate(&self, game_state: &mut GameState, tick: u64) {
+		game_state.tick(self.delta);
+		for (player, inputs) in self.inputs.iter().enumerate() {
+			for e in inputs.get(&tick).into_iter().flatten() {
+				game_state.input_event_for(player, e.clone());
+			}
+		}
+	}
+	
+	pub fn advance(&mut self, game_state: &mut GameState, events: Vec<InputEvent>) -> String { //runs one tick with our events, returns the message to send
+		let mut msg = format!("tick {}", self.tick);
+		for e in events.iter() {
+			msg.push('\n');
+			msg.push_str(&event_to_string(e));
+		}
+		for (t, h) in self.hashes.hashes.range(self.sent_hashes..) {
+			msg.push_str(&format!("\nhash {} {}", t, to_hex(h)));
+		}
+		self.sent_hashes = self.hashes.hashes.keys().last().map(|x| x + 1).unwrap_or(0);
+		self.snapshots.push_back((self.tick, snapshot(game_state)));
+		self.inputs[self.local].insert(self.tick, events);
+		self.simulate(game_state, self.tick);
+		self.tick += 1;
+		msg
+	}
+	
+	pub fn receive(&mut self, game_state: &mut GameState, msg: &str) -> Result<(), String> {
+		let mut lines = msg.lines();
+		let tick = lines.next().and_then(|x| x.strip_prefix("tick ")).and_then(|x| x.parse::<u64>().ok()).ok_or_else(|| format!("bad message: {}", msg))?;
+		if tick < self.remote_confirmed {
+			return Err(format!("got tick {} twice", tick));
+		}
+		let mut events = Vec::new();
+		for line in lines {
+			let mut parts = line.splitn(2, ' ');
+			match (parts.next().unwrap_or(""), parts.next()) {
+				("hash", Some(x)) => {
+					let mut parts = x.splitn(2, ' ');
+					let t = parts.next().and_then(|x| x.parse::<u64>().ok()).ok_or_else(|| format!("bad hash: {}", line))?;
+					self.remote_hashes.insert(t, parse_hash(parts.next().unwrap_or(""))?);
+				},
+				(kind, arg) => events.push(parse_event(kind, arg)?),
+			}
+		}
+		let predicted = events.is_empty(); //we guessed no new events, so if that's what happened there's nothing to redo
+		let remote = self.remote();
+		self.inputs[remote].insert(tick, events);
+		self.remote_confirmed = tick + 1;
+		if tick < self.tick && !predicted {
+			let i = self.snapshots.iter().position(|x| x.0 == tick).ok_or_else(|| format!("no snapshot for tick {}, too far behind", tick))?;
+			restore(game_state, &self.snapshots[i].1);
+			self.snapshots.truncate(i);
+			let end = self.tick;
+			for t in tick..end {
+				self.snapshots.push_back((t, snapshot(game_state)));
+				self.simulate(game_state, t);
+			}
+			self.rollbacks += 1;
+		}
+		while self.snapshots.front().map(|x| x.0 < self.remote_confirmed).unwrap_or(false) { //everything before here is final
+			let (t, s) = self.snapshots.pop_front().unwrap();
+			self.hashes.record(t, &s);
+		}
+		let (hashes, mut desync) = (&self.hashes.hashes, self.desync);
+		self.remote_hashes.retain(|t, h| match hashes.get(t) { //compare whatever we can, keep the rest for later
+			Some(x) => {
+				if x != h && desync.map(|d| *t < d).unwrap_or(true) {
+					desync = Some(*t);
+				}
+				false
+			},
+			None => true,
+		});
+		self.desync = desync;
+		let confirmed = self.remote_confirmed;
+		self.inputs.iter_mut().for_each(|x| x.retain(|&t, _| t >= confirmed));
+		Ok(())
+	}
+}
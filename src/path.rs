@@ -0,0 +1,85 @@
+use crate::prelude::*;
+
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub enum PathMode {
+	Loop, //last waypoint goes back to the first
+	PingPong, //walk the waypoints forwards then backwards
+}
+
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub enum Easing {
+	Linear,
+	Smooth, //slow down into and out of each waypoint
+}
+
+impl Default for PathMode {
+	fn default() -> Self { PathMode::Loop }
+}
+
+impl Default for Easing {
+	fn default() -> Self { Easing::Linear }
+}
+
+impl Easing {
+	pub fn apply(self, t: f64) -> f64 {
+		match self {
+			Easing::Linear => t,
+			Easing::Smooth => t * t * (3.0 - 2.0 * t),
+		}
+	}
+}
+
+#[derive(Debug,Default,Copy,Clone)]
+pub struct Waypoint {
+	pub pos: Vec2<f64>,
+	pub duration: f64, //ticks taken to get from here to the next waypoint
+	pub easing: Easing,
+}
+
+#[derive(Debug,Default,Clone)]
+pub struct PlatformPath {
+	pub waypoints: Vec<Waypoint>,
+	pub mode: PathMode,
+	pub phase: f64, //added to the time, so platforms sharing a path don't have to move together
+}
+
+impl PlatformPath {
+	pub fn new(waypoints: Vec<Waypoint>, mode: PathMode) -> Self {
+		Self {
+			waypoints,
+			mode,
+			phase: 0.0,
+		}
+	}
+	
+	fn legs(&self) -> Vec<(Waypoint, Vec2<f64>)> { //each leg is a waypoint and where it's heading
+		let w = &self.waypoints;
+		let n = w.len();
+		match self.mode {
+			PathMode::Loop => (0..n).map(|i| (w[i], w[(i + 1) % n].pos)).collect(),
+			PathMode::PingPong => (0..n-1).map(|i| (w[i], w[i + 1].pos))
+				.chain((1..n).rev().map(|i| (Waypoint { pos: w[i].pos, ..w[i - 1] }, w[i - 1].pos))) //going backwards takes as long as going forwards did
+				.collect(),
+		}
+	}
+	
+	pub fn pos_at(&self, time: f64) -> Vec2<f64> {
+		if self.waypoints.len() < 2 {
+			return self.waypoints.first().map(|x| x.pos).unwrap_or(Vec2::zero());
+		}
+		let legs = self.legs();
+		let total = legs.iter().map(|x| x.0.duration.max(0.0)).sum::<f64>();
+		if total <= 0.0 {
+			return self.waypoints[0].pos;
+		}
+		let mut t = (time + self.phase).rem_euclid(total);
+		for &(from, to) in legs.iter() {
+			let d = from.duration.max(0.0);
+			if t < d {
+				return lerp(from.pos, to, from.easing.apply(t / d));
+			}
+			t -= d;
+		}
+		legs[legs.len()-1].1
+	}
+}
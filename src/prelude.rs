@@ -1,11 +1,24 @@
+#[cfg(feature = "web")]
 pub use web_sys::WebGl2RenderingContext as GL;
-pub use crate::vertex::*;
 pub use std::rc::{Rc,Weak};
 pub use std::cell::{Cell,RefCell,Ref,RefMut};
 pub use std::collections::{HashSet,HashMap};
+#[cfg(feature = "web")]
 pub use crate::boiler_plate::*;
 pub use crate::vertex::*;
 pub use crate::game_state::*;
+pub use crate::timestep::*;
+pub use crate::broadphase::*;
+pub use crate::path::*;
+pub use crate::level::*;
+pub use crate::replay::*;
+pub use crate::save::*;
+pub use crate::netcode::*;
+pub use crate::desync::*;
+pub use crate::ai::*;
+pub use crate::nav::*;
+pub use crate::reach::*;
+pub use crate::waves::*;
 
 pub use math_lib::{vec2::*,vec3::*,traits::*};
 pub use array_tuple::ArrayTuple;
@@ -19,23 +32,28 @@ pub macro l() {
 	&concat!(file!(), " ", line!())
 }
 
-pub fn binomial(n: usize) -> f64 {
-	let mut r = 0.0;
-	for _ in 0..n {
-		r += randf();
-	}
-	r / n as f64
+pub fn lerp(a: Vec2<f64>, b: Vec2<f64>, t: f64) -> Vec2<f64> {
+	a + (b - a) * t
 }
 
-pub fn randf() -> f64 {
-	randi() as f64 / u64::max_value() as f64
+pub fn dot(a: Vec2<f64>, b: Vec2<f64>) -> f64 {
+	a.x * b.x + a.y * b.y
 }
 
-pub fn randi() -> u64 {
-	let mut x = [0; 8];
-	window().crypto().expect(l!()).get_random_values_with_u8_array(&mut x).expect(l!());
-	//log!("random values: {:?}",x);
-	unsafe { std::mem::transmute(x) }
+pub trait Rng { //source of randomness for the simulation, so it doesn't have to know about the browser
+	fn next(&mut self) -> u64;
+	
+	fn f64(&mut self) -> f64 {
+		self.next() as f64 / u64::max_value() as f64
+	}
+	
+	fn binomial(&mut self, n: usize) -> f64 {
+		let mut r = 0.0;
+		for _ in 0..n {
+			r += self.f64();
+		}
+		r / n as f64
+	}
 }
 
 #[derive(Debug,Copy,Clone)]
@@ -51,14 +69,19 @@ impl RandGen {
 		}
 	}
 	
+	pub fn state(&self) -> [u64; 5] { self.state }
+	pub fn from_state(state: [u64; 5]) -> Self { Self { state } }
+	
 	pub fn skip(&mut self, n: usize) -> Self {
 		for _ in 0..n {
 			self.next();
 		}
 		*self
 	}
-	
-	pub fn next(&mut self) -> u64 {
+}
+
+impl Rng for RandGen {
+	fn next(&mut self) -> u64 {
 		let mut t = self.state[3];
 		t ^= t >> 2;
 		t ^= t << 1;
@@ -67,12 +90,8 @@ impl RandGen {
 		t ^= s;
 		t ^= s << 4;
 		self.state[0] = t;
-		self.state[4] += 362437;
-		t + self.state[4]
-	}
-	
-	pub fn f64(&mut self) -> f64 {
-		self.next() as f64 / u64::max_value() as f64
+		self.state[4] = self.state[4].wrapping_add(362437);
+		t.wrapping_add(self.state[4])
 	}
 }
 
@@ -0,0 +1,63 @@
+use crate::prelude::*;
+
+//works out which parts of a level the player can actually get to, using the nav graph built with the player's movement instead of an enemy's
+//same limits as the nav graph: moving platforms and bridges aren't planned around so they get reported separately rather than guessed at
+
+#[derive(Debug,Clone,Default)]
+pub struct Reachability {
+	pub reachable: Vec<bool>, //one per platform
+	pub unchecked: Vec<usize>, //platforms the graph leaves out
+	pub goal: Option<bool>, //None if the level has no goal
+}
+
+impl Reachability {
+	pub fn unreachable(&self) -> Vec<usize> {
+		(0..self.reachable.len()).filter(|i| !self.reachable[*i] && !self.unchecked.contains(i)).collect()
+	}
+}
+
+fn can_touch(n: &NavNode, goal: &Goal, tracer: &Tracer) -> bool { //whether standing on n or jumping or falling off it gets any of us into goal, without going through anything on the way
+	let (g0, g1) = (goal.pos(), goal.pos() + goal.size());
+	let (hw, h) = (tracer.envelope.size.x / 2.0, tracer.envelope.size.y);
+	if n.left - hw < g1.x && n.right + hw > g0.x && n.y < g1.y && n.y + h > g0.y {
+		return true; //just walking along it
+	}
+	let touches = |feet: Vec2<f64>, falling: bool| {
+		if feet.x - hw < g1.x && feet.x + hw > g0.x && feet.y < g1.y && feet.y + h > g0.y {
+			Some(true)
+		} else if falling && feet.y + h < g0.y {
+			Some(false) //gone past it
+		} else {
+			None
+		}
+	};
+	tracer.clear(n, true, (g0.x - hw, g1.x + hw), g0.y - h, None, touches) || tracer.clear(n, false, (g0.x - hw, g1.x + hw), g0.y - h, None, touches)
+}
+
+pub fn reachability(game_state: &GameState) -> Result<Reachability, String> {
+	let platforms = &game_state.platforms;
+	let envelope = Envelope::player(&game_state.player);
+	let nav = NavGraph::new(platforms, &envelope);
+	let start = nav.surface_below(game_state.player_start - vec2(0.0, envelope.size.y / 2.0)).ok_or("the start isn't above any platform")?;
+	let mut seen = vec![false; nav.nodes.len()];
+	seen[start] = true;
+	let mut todo = vec![start];
+	while let Some(i) = todo.pop() {
+		for l in nav.links[i].iter() {
+			if !seen[l.to] {
+				seen[l.to] = true;
+				todo.push(l.to);
+			}
+		}
+	}
+	let mut reachable = vec![false; platforms.len()];
+	for (i, n) in nav.nodes.iter().enumerate() {
+		reachable[n.platform] = seen[i];
+	}
+	let tracer = Tracer::new(platforms, &envelope);
+	Ok(Reachability {
+		reachable,
+		unchecked: (0..platforms.len()).filter(|&i| platforms[i].path.is_some() || platforms[i].bridge.is_some()).collect(),
+		goal: game_state.goal.as_ref().map(|g| nav.nodes.iter().enumerate().any(|(i, n)| seen[i] && can_touch(n, g, &tracer))),
+	})
+}
@@ -0,0 +1,135 @@
+use crate::prelude::*;
+use std::fmt;
+
+#[derive(Debug,Clone,Default)]
+pub struct Recording { //everything needed to re-run a game from its starting state
+	pub seed: u64,
+	pub delta: f64,
+	pub inputs: Vec<Vec<InputEvent>>, //events that came in after each tick
+	pub hashes: HashStream, //what the state looked like while recording, so replays can check they're still in sync
+}
+
+impl Recording {
+	pub fn new(seed: u64, delta: f64) -> Self {
+		Self {
+			seed,
+			delta,
+			inputs: Vec::new(),
+			hashes: HashStream::default(),
+		}
+	}
+	
+	pub fn ticks(&self) -> usize { self.inputs.len() }
+	
+	pub fn parse(src: &str) -> Result<Self, String> {
+		let mut r = Self::default();
+		for (i, line) in src.lines().enumerate() {
+			let error = |msg: &str| format!("line {}: {}", i + 1, msg);
+			let mut parts = line.splitn(3, ' '); //keys can be a space, so the last part is everything left
+			let (a, b, c) = (parts.next().unwrap_or(""), parts.next(), parts.next());
+			match (a, b) {
+				("", None) => {},
+				("seed", Some(x)) => r.seed = x.parse().map_err(|_| error("bad seed"))?,
+				("delta", Some(x)) => r.delta = x.parse().map_err(|_| error("bad delta"))?,
+				("ticks", Some(x)) => r.inputs = vec![Vec::new(); x.parse().map_err(|_| error("bad tick count"))?],
+				("hash", Some(x)) => {
+					let tick = x.parse::<u64>().map_err(|_| error("bad hash tick"))?;
+					r.hashes.hashes.insert(tick, parse_hash(c.unwrap_or("")).map_err(|e| error(&e))?);
+				},
+				(tick, Some(kind)) => {
+					let tick = tick.parse::<usize>().map_err(|_| error("expected a tick number"))?;
+					let e = parse_event(kind, c).map_err(|e| error(&e))?;
+					r.inputs.get_mut(tick).ok_or_else(|| error("event after the last tick"))?.push(e);
+				},
+				_ => return Err(error("expected seed, delta, ticks, a hash or an event")),
+			}
+		}
+		Ok(r)
+	}
+}
+
+impl fmt::Display for Recording { //one event per line, prefixed by the tick it came in on
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "seed {}\ndelta {}\nticks {}\n", self.seed, self.delta, self.inputs.len())?;
+		for (t, h) in self.hashes.hashes.iter() {
+			writeln!(f, "hash {} {}", t, to_hex(h))?;
+		}
+		for (i, events) in self.inputs.iter().enumerate() {
+			for e in events.iter() {
+				writeln!(f, "{} {}", i, event_to_string(e))?;
+			}
+		}
+		Ok(())
+	}
+}
+
+pub fn event_to_string(e: &InputEvent) -> String { //shared with netcode, so inputs look the same on the wire as in recordings
+	match e {
+		InputEvent::Wheel(x) => format!("wheel {}", x),
+		InputEvent::LeftClick(p) => format!("left_click {} {}", p.x, p.y),
+		InputEvent::RightClick => "right_click".to_string(),
+		InputEvent::KeyDown(k) => format!("down {}", k),
+		InputEvent::KeyUp(k) => format!("up {}", k),
+	}
+}
+
+pub fn parse_event(kind: &str, arg: Option<&str>) -> Result<InputEvent, String> {
+	Ok(match (kind, arg) {
+		("wheel", Some(x)) => InputEvent::Wheel(x.parse().map_err(|_| "bad wheel amount".to_string())?),
+		("left_click", Some(x)) => {
+			let p = x.split(' ').map(|x| x.parse::<f64>()).collect::<Result<Vec<_>, _>>().map_err(|_| "bad click position".to_string())?;
+			match p[..] {
+				[x, y] => InputEvent::LeftClick(vec2(x, y)),
+				_ => return Err("click needs an x and a y".to_string()),
+			}
+		},
+		("right_click", None) => InputEvent::RightClick,
+		("down", Some(k)) => InputEvent::KeyDown(k.to_string()),
+		("up", Some(k)) => InputEvent::KeyUp(k.to_string()),
+		_ => return Err("unknown event".to_string()),
+	})
+}
+
+#[derive(Debug)]
+pub struct Recorder { //wraps ticking the game so every input gets written down
+	pub recording: Recording,
+}
+
+impl Recorder {
+	pub fn new(game_state: &mut GameState, seed: u64, delta: f64) -> Self {
+		game_state.seed(seed);
+		Self {
+			recording: Recording::new(seed, delta),
+		}
+	}
+	
+	pub fn tick(&mut self, game_state: &mut GameState, events: Vec<InputEvent>) {
+		game_state.tick(self.recording.delta);
+		for e in events.iter() {
+			game_state.input_event(e.clone());
+		}
+		self.recording.inputs.push(events);
+		self.recording.hashes.record(self.recording.ticks() as u64, game_state);
+	}
+}
+
+pub fn replay(game_state: &mut GameState, recording: &Recording) -> HashStream { //game_state should be the same as when recording started, eg freshly loaded from the same level
+	game_state.seed(recording.seed);
+	let mut hashes = HashStream::default();
+	for (i, events) in recording.inputs.iter().enumerate() {
+		game_state.tick(recording.delta);
+		for e in events.iter() {
+			game_state.input_event(e.clone());
+		}
+		hashes.record(i as u64 + 1, game_state);
+	}
+	hashes
+}
+
+pub fn check_replay(game_state: &mut GameState, recording: &Recording) -> Result<(), String> { //replays and complains if it doesn't end up where the recording did
+	let hashes = replay(game_state, recording);
+	match recording.hashes.first_divergence(&hashes) {
+		Some(t) => Err(format!("replay desynced between tick {} and tick {}", recording.hashes.last_agreement(&hashes).unwrap_or(0), t)),
+		None => Ok(()),
+	}
+}
@@ -0,0 +1,295 @@
+use crate::prelude::*;
+use sha3::{Digest, Sha3_256};
+
+//save layout: MAGIC, VERSION as u32, the game state, then sha3-256 of SAVE_KEY followed by everything before it
+//bump VERSION whenever anything saved changes shape, old saves get refused rather than misread
+const MAGIC: &[u8; 4] = b"SMST";
+const VERSION: u32 = 12;
+const DIGEST_LEN: usize = 32;
+const SAVE_KEY: &[u8] = b"seamstress save"; //not really secret since it ships with the game, but it stops casual hex editing
+
+#[derive(Debug,Default)]
+pub struct Writer {
+	pub buf: Vec<u8>,
+}
+
+pub struct Reader<'a> {
+	buf: &'a [u8],
+	pos: usize,
+}
+
+impl<'a> Reader<'a> {
+	pub fn new(buf: &'a [u8]) -> Self {
+		Self { buf, pos: 0 }
+	}
+	
+	pub fn bytes(&mut self, n: usize) -> Result<&'a [u8], String> {
+		let r = self.buf.get(self.pos..self.pos + n).ok_or_else(|| format!("save ends early, wanted {} bytes at {}", n, self.pos))?;
+		self.pos += n;
+		Ok(r)
+	}
+	
+	pub fn finished(&self) -> bool { self.pos == self.buf.len() }
+}
+
+pub trait Save: Sized {
+	fn save(&self, w: &mut Writer);
+	fn load(r: &mut Reader) -> Result<Self, String>;
+}
+
+macro save_num($($t: ty,)*) {
+	$(impl Save for $t {
+		fn save(&self, w: &mut Writer) { w.buf.extend_from_slice(&self.to_le_bytes()); }
+		fn load(r: &mut Reader) -> Result<Self, String> {
+			let mut x = [0; std::mem::size_of::<$t>()];
+			x.copy_from_slice(r.bytes(x.len())?);
+			Ok(<$t>::from_le_bytes(x))
+		}
+	})*
+}
+save_num!(u8,i8,u32,u64,f64,);
+
+impl Save for usize {
+	fn save(&self, w: &mut Writer) { (*self as u64).save(w) }
+	fn load(r: &mut Reader) -> Result<Self, String> { Ok(u64::load(r)? as usize) }
+}
+
+impl Save for bool {
+	fn save(&self, w: &mut Writer) { (*self as u8).save(w) }
+	fn load(r: &mut Reader) -> Result<Self, String> {
+		match u8::load(r)? {
+			0 => Ok(false),
+			1 => Ok(true),
+			x => Err(format!("bad bool {}", x)),
+		}
+	}
+}
+
+impl<T: Save> Save for Vec2<T> {
+	fn save(&self, w: &mut Writer) { self.x.save(w); self.y.save(w); }
+	fn load(r: &mut Reader) -> Result<Self, String> { Ok(Vec2 { x: T::load(r)?, y: T::load(r)? }) }
+}
+
+impl<T: Save> Save for Option<T> {
+	fn save(&self, w: &mut Writer) {
+		self.is_some().save(w);
+		if let Some(x) = self { x.save(w); }
+	}
+	fn load(r: &mut Reader) -> Result<Self, String> {
+		Ok(if bool::load(r)? { Some(T::load(r)?) } else { None })
+	}
+}
+
+impl<T: Save> Save for Vec<T> {
+	fn save(&self, w: &mut Writer) {
+		self.len().save(w);
+		self.iter().for_each(|x| x.save(w));
+	}
+	fn load(r: &mut Reader) -> Result<Self, String> {
+		let n = usize::load(r)?;
+		if n > r.buf.len() { //every element is at least a byte, so this can only be a corrupt length
+			return Err(format!("bad length {}", n));
+		}
+		(0..n).map(|_| T::load(r)).collect()
+	}
+}
+
+impl Save for [bool; 4] {
+	fn save(&self, w: &mut Writer) { self.iter().for_each(|x| x.save(w)); }
+	fn load(r: &mut Reader) -> Result<Self, String> { Ok([bool::load(r)?, bool::load(r)?, bool::load(r)?, bool::load(r)?]) }
+}
+
+impl Save for [u64; 5] {
+	fn save(&self, w: &mut Writer) { self.iter().for_each(|x| x.save(w)); }
+	fn load(r: &mut Reader) -> Result<Self, String> { Ok([u64::load(r)?, u64::load(r)?, u64::load(r)?, u64::load(r)?, u64::load(r)?]) }
+}
+
+impl Save for RandGen {
+	fn save(&self, w: &mut Writer) { self.state().save(w) }
+	fn load(r: &mut Reader) -> Result<Self, String> { Ok(RandGen::from_state(Save::load(r)?)) }
+}
+
+macro save_enum($t: ident, $($variant: ident = $n: literal,)*) {
+	impl Save for $t {
+		fn save(&self, w: &mut Writer) {
+			match self { $($t::$variant => ($n as u8).save(w),)* }
+		}
+		fn load(r: &mut Reader) -> Result<Self, String> {
+			match u8::load(r)? {
+				$($n => Ok($t::$variant),)*
+				x => Err(format!("bad {} {}", stringify!($t), x)),
+			}
+		}
+	}
+}
+save_enum!(Collision, Solid = 0, OneWay = 1,);
+save_enum!(PathMode, Loop = 0, PingPong = 1,);
+save_enum!(Easing, Linear = 0, Smooth = 1,);
+save_enum!(AiState, Idle = 0, Patrol = 1, Chase = 2, Jump = 3, Flee = 4,);
+save_enum!(EnemyKind, Walker = 0, Flyer = 1, Shooter = 2,);
+
+macro save_struct($t: ident { $($field: ident,)* }) { //saves every listed field in order, anything not listed comes from Default, the same fields get dumped for desync diffs
+	impl Save for $t {
+		fn save(&self, w: &mut Writer) {
+			$(self.$field.save(w);)*
+		}
+		fn load(r: &mut Reader) -> Result<Self, String> {
+			let mut x = $t::default();
+			$(x.$field = Save::load(r)?;)*
+			Ok(x)
+		}
+	}
+	impl Dump for $t {
+		fn dump(&self, path: &str, out: &mut Vec<(String, String)>) {
+			$(self.$field.dump(&format!("{}.{}", path, stringify!($field)), out);)*
+		}
+	}
+}
+save_struct!(Waypoint { pos, duration, easing, });
+save_struct!(PlatformPath { waypoints, mode, phase, });
+save_struct!(Bridge { expires, durability, });
+save_struct!(Platform { centre_pos, size, collision, vel, path, shape, bridge, });
+save_struct!(Hazard { centre_pos, size, damage, });
+save_struct!(Goal { centre_pos, size, });
+save_struct!(Spawner { pos, kind, interval, cap, next_spawn, });
+save_struct!(WaveParams { wave_ticks, interval_scale, min_interval, cap_waves, toughness, jitter, });
+save_struct!(WaveDirector { params, rng, });
+save_struct!(Enemy { kind, centre_pos, prev_pos, vel, grounded, start_time, hp, stitched_until, stitched_area, ai, ai_dir, ai_since, fired, toughness, spawner, });
+save_struct!(Projectile { centre_pos, prev_pos, vel, damage, expires, });
+save_struct!(Thread { from, to, time, delta, detonated, prev_from, length, });
+save_struct!(Grapple { needle, vel, length, fired, });
+save_struct!(Player { centre_pos, prev_pos, vel, movement, grounded, hp, thread, bob, bob_dir, grapple, reeling, });
+save_struct!(GameState { player, coop, player_start, platforms, enemies, projectiles, spawners, waves, hazards, goal, time, rng, }); //not the camera, each co-op player's follows them so it'd make their hashes differ
+
+fn digest(data: &[u8]) -> Vec<u8> {
+	let mut h = Sha3_256::new();
+	h.update(SAVE_KEY);
+	h.update(data);
+	h.finalize().to_vec()
+}
+
+pub fn save_game(game_state: &GameState) -> Vec<u8> {
+	let mut w = Writer::default();
+	w.buf.extend_from_slice(MAGIC);
+	VERSION.save(&mut w);
+	game_state.save(&mut w);
+	let d = digest(&w.buf);
+	w.buf.extend_from_slice(&d);
+	w.buf
+}
+
+pub fn load_game(data: &[u8]) -> Result<GameState, String> {
+	if data.len() < MAGIC.len() + 4 + DIGEST_LEN || &data[..MAGIC.len()] != MAGIC {
+		return Err("not a save".to_string());
+	}
+	let (body, d) = data.split_at(data.len() - DIGEST_LEN);
+	if digest(body) != d {
+		return Err("save is corrupt or has been tampered with".to_string());
+	}
+	let mut r = Reader::new(&body[MAGIC.len()..]);
+	let version = u32::load(&mut r)?;
+	if version != VERSION {
+		return Err(format!("save is from version {}, we only load version {}", version, VERSION));
+	}
+	let mut game_state = GameState::load(&mut r)?;
+	if !r.finished() {
+		return Err("save has junk on the end".to_string());
+	}
+	game_state.build_nav();
+	game_state.cam_pos = game_state.player.centre_pos;
+	game_state.prev_cam_pos = game_state.cam_pos;
+	Ok(game_state)
+}
+
+pub fn to_hex(data: &[u8]) -> String { //for storing saves as text, eg in localStorage
+	data.iter().map(|x| format!("{:02x}", x)).collect()
+}
+
+pub fn from_hex(s: &str) -> Result<Vec<u8>, String> {
+	if s.len() % 2 != 0 || !s.is_ascii() {
+		return Err("bad hex".to_string());
+	}
+	(0..s.len()).step_by(2).map(|i| u8::from_str_radix(&s[i..i+2], 16).map_err(|e| e.to_string())).collect()
+}
+
+#[cfg(test)]
+mod tests { //native only: cargo test --no-default-features
+	use super::*;
+	
+	fn busy_state() -> GameState { //something of everything that gets saved
+		let mut r = parse_level(FIRST_LEVEL).unwrap();
+		r.seed(99);
+		r.add_coop_player();
+		r.platforms.push(Platform {
+			path: Some(PlatformPath {
+				waypoints: vec![Waypoint { pos: vec2(0.0, 1.0), duration: 60.0, easing: Easing::Smooth }, Waypoint { pos: vec2(1.0, 1.0), duration: 30.0, easing: Easing::Linear }],
+				mode: PathMode::PingPong,
+				phase: 12.5,
+			}),
+			.. Platform::from_polygon(vec![vec2(0.0, 1.0), vec2(0.3, 1.0), vec2(0.3, 1.1)])
+		});
+		r.platforms.push(Platform {
+			centre_pos: vec2(0.2, -0.3),
+			size: vec2(0.5, 0.02),
+			collision: Collision::OneWay,
+			bridge: Some(Bridge { expires: 700.0, durability: 123.0 }),
+			.. Default::default()
+		});
+		r.input_event(InputEvent::KeyDown("d".to_string()));
+		for _ in 0..250 { //long enough for the spawner to go off and the thread to get long
+			r.tick(1.0);
+		}
+		r.enemies.push(Enemy { centre_pos: vec2(0.3, 0.4), .. Enemy::new(EnemyKind::Shooter, r.time) });
+		r.enemies[0].stitch(0.01, r.time);
+		r.projectiles.push(Projectile::new(vec2(0.1, 0.2), vec2(0.01, 0.0), 5.0, r.time));
+		r.player.grapple = Some(Grapple { needle: vec2(0.5, 0.9), vel: Vec2::zero(), length: 0.4, fired: r.time - 20.0 });
+		r.player.thread[0].detonate(0, r.time);
+		r
+	}
+	
+	fn signed(mut body: Vec<u8>) -> Vec<u8> {
+		let d = digest(&body);
+		body.extend_from_slice(&d);
+		body
+	}
+	
+	#[test]
+	fn round_trip() {
+		let game_state = busy_state();
+		assert!(game_state.enemies.len() >= 2 && game_state.player.thread.len() > 10);
+		let loaded = load_game(&save_game(&game_state)).unwrap();
+		assert_eq!(first_difference(&game_state, &loaded), None);
+		assert!(save_game(&loaded) == save_game(&game_state));
+		assert_eq!(loaded.nav_for(EnemyKind::Walker).nodes.len(), game_state.nav_for(EnemyKind::Walker).nodes.len()); //not saved, rebuilt on load
+	}
+	
+	#[test]
+	fn flipped_byte_is_refused() {
+		let mut data = save_game(&busy_state());
+		let i = MAGIC.len() + 4 + 100;
+		data[i] ^= 1;
+		let e = load_game(&data).unwrap_err();
+		assert!(e.contains("corrupt or has been tampered with"), "{}", e);
+	}
+	
+	#[test]
+	fn wrong_version_is_refused() { //signed properly, so it's the version check that has to catch it
+		let mut w = Writer::default();
+		w.buf.extend_from_slice(MAGIC);
+		(VERSION + 1).save(&mut w);
+		busy_state().save(&mut w);
+		let e = load_game(&signed(w.buf)).unwrap_err();
+		assert!(e.contains("version"), "{}", e);
+	}
+	
+	#[test]
+	fn trailing_junk_is_refused() {
+		let mut w = Writer::default();
+		w.buf.extend_from_slice(MAGIC);
+		VERSION.save(&mut w);
+		busy_state().save(&mut w);
+		w.buf.push(0);
+		let e = load_game(&signed(w.buf)).unwrap_err();
+		assert!(e.contains("junk"), "{}", e);
+	}
+}
@@ -0,0 +1,36 @@
+#[derive(Debug,Copy,Clone)]
+pub struct FixedStep { //turns real frame times into a whole number of fixed length ticks
+	pub step: f64, //real milliseconds per tick
+	pub max_ticks: usize, //most ticks we'll run in one frame before giving up on catching up
+	accumulator: f64,
+	last: Option<f64>,
+}
+
+impl FixedStep {
+	pub fn new(step: f64, max_ticks: usize) -> Self {
+		Self {
+			step,
+			max_ticks,
+			accumulator: 0.0,
+			last: None,
+		}
+	}
+	
+	pub fn advance(&mut self, now: f64) -> usize { //returns how many ticks to run this frame
+		let elapsed = self.last.map(|x| (now - x).max(0.0)).unwrap_or(0.0);
+		self.last = Some(now);
+		self.accumulator += elapsed;
+		let ticks = (self.accumulator / self.step) as usize;
+		if ticks > self.max_ticks { //we're too far behind to ever catch up (tab was in the background etc), so drop the backlog instead of spiralling
+			self.accumulator = 0.0;
+			self.max_ticks
+		} else {
+			self.accumulator -= ticks as f64 * self.step;
+			ticks
+		}
+	}
+	
+	pub fn alpha(&self) -> f64 { //how far we are between the last tick and the next one, for blending positions when rendering
+		(self.accumulator / self.step).min(1.0)
+	}
+}
@@ -40,6 +40,25 @@ pub fn make_quad(pos: Vec2<f64>, z: usize, size: Vec2<f64>, color: [[f32; 4]; 4]
 	quadify(r)
 }
 
+pub fn triangle(v: &mut Vec<Vertex>, points: [Vec2<f64>; 3], z: usize, color: [[f32; 4]; 3], shine_color: [[f32; 4]; 3], start_time: [[f64; 3]; 3], shine_rate: f64, shine_bias: [f64; 3]) {
+	for i in 0..3 {
+		v.push(Vertex {
+			pos: points[i].extend(get_z(z)).f32(),
+			color: color[i],
+			shine_color: shine_color[i],
+			start_time: [start_time[i][0] as f32, start_time[i][1] as f32, start_time[i][2] as f32],
+			shine_rate: shine_rate as f32,
+			shine_bias: [shine_bias[0] as f32, shine_bias[1] as f32, shine_bias[2] as f32],
+		});
+	}
+}
+
+pub fn polygon(v: &mut Vec<Vertex>, points: &[Vec2<f64>], z: usize, color: [f32; 4], shine_color: [f32; 4], start_time: [f64; 3], shine_rate: f64, shine_bias: [f64; 3]) { //convex only, drawn as a fan from the first point
+	for i in 1..points.len().saturating_sub(1) {
+		triangle(v, [points[0], points[i], points[i+1]], z, [color; 3], [shine_color; 3], [start_time; 3], shine_rate, shine_bias);
+	}
+}
+
 pub fn get_z(z: usize) -> f64 { z as f64 / 1e4 }
 
 /*#[derive(Debug)]
@@ -0,0 +1,106 @@
+use crate::prelude::*;
+
+//the wave director makes spawners speed up and enemies toughen as a level goes on, if the level asks for it with a waves line
+//by default it leaves everything alone, spawners keep their interval and cap and every enemy is as tough as the first
+//it has its own rng, seeded alongside the game's, so when spawners try to spawn only depends on the seed and the level, not on whatever randomness the fighting used up
+//that's what lets spawn_schedule say ahead of time exactly what a run will spawn
+
+#[derive(Debug,Copy,Clone)]
+pub struct WaveParams {
+	pub wave_ticks: f64, //how long each wave lasts
+	pub interval_scale: f64, //spawn intervals get multiplied by this each wave
+	pub min_interval: f64, //fraction of a spawner's interval it never goes below
+	pub cap_waves: f64, //every this many waves each spawner's cap goes up by one
+	pub toughness: f64, //extra max hp per wave, as a fraction of the normal amount
+	pub jitter: f64, //spawn intervals vary randomly by up to this fraction either way
+}
+
+impl Default for WaveParams {
+	fn default() -> Self {
+		Self {
+			wave_ticks: 1200.0,
+			interval_scale: 1.0,
+			min_interval: 0.3,
+			cap_waves: f64::INFINITY,
+			toughness: 0.0,
+			jitter: 0.0,
+		}
+	}
+}
+
+impl WaveParams {
+	pub fn escalating(wave_ticks: f64, interval_scale: f64, toughness: f64) -> Self { //what a waves line turns on, caps grow and intervals vary too
+		Self {
+			wave_ticks,
+			interval_scale,
+			min_interval: 0.3,
+			cap_waves: 2.0,
+			toughness,
+			jitter: 0.2,
+		}
+	}
+}
+
+#[derive(Debug,Clone)]
+pub struct WaveDirector {
+	pub params: WaveParams,
+	pub rng: RandGen,
+}
+
+impl Default for WaveDirector {
+	fn default() -> Self {
+		Self {
+			params: WaveParams::default(),
+			rng: RandGen::new(0),
+		}
+	}
+}
+
+impl WaveDirector {
+	pub fn reseed(&mut self, seed: u64) { //RandGen barely mixes its seed, so nearby seeds would give the same schedule for the first few waves without scrambling it first (splitmix64)
+		let mut z = seed.wrapping_add(0x9e3779b97f4a7c15);
+		z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+		self.rng = RandGen::new(z ^ (z >> 31));
+	}
+	
+	pub fn wave(&self, time: f64) -> f64 { (time / self.params.wave_ticks).floor() } //0 for the first wave
+	
+	pub fn interval(&self, base: f64, time: f64) -> f64 {
+		(base * self.params.interval_scale.powf(self.wave(time))).max(base * self.params.min_interval)
+	}
+	
+	pub fn next_interval(&mut self, base: f64, time: f64) -> f64 { //interval with some randomness, so spawners don't all line up
+		self.interval(base, time) * (1.0 + self.params.jitter * (self.rng.f64() * 2.0 - 1.0))
+	}
+	
+	pub fn cap(&self, base: usize, time: f64) -> usize {
+		base + (self.wave(time) / self.params.cap_waves).floor() as usize
+	}
+	
+	pub fn toughness(&self, time: f64) -> f64 { 1.0 + self.wave(time) * self.params.toughness }
+}
+
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub struct SpawnEvent {
+	pub time: f64,
+	pub spawner: usize,
+	pub kind: EnemyKind,
+	pub toughness: f64,
+}
+
+pub fn spawn_schedule(game_state: &GameState, until: f64) -> Vec<SpawnEvent> { //every spawn a run would make up to until, ticking with delta 1, if the caps never got in the way
+	let mut waves = game_state.waves.clone();
+	let mut spawners = game_state.spawners.clone();
+	let mut r = Vec::new();
+	let mut time = game_state.time;
+	while time < until {
+		time += 1.0;
+		for (i, s) in spawners.iter_mut().enumerate() {
+			if let Some(e) = s.spawn(time, 0, &mut waves) {
+				r.push(SpawnEvent { time, spawner: i, kind: e.kind, toughness: e.toughness });
+			}
+		}
+	}
+	r
+}
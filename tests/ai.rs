@@ -0,0 +1,123 @@
+#![cfg(not(feature = "web"))]
+//scripted scenarios on the headless sim, each one a small level with the player standing somewhere and one enemy
+mod common;
+use common::*;
+use seamstress::prelude::*;
+
+fn arena(platforms: Vec<Platform>, player: Vec2<f64>, enemy: Vec2<f64>) -> GameState {
+	let mut r = GameState::new();
+	r.platforms = platforms;
+	r.build_nav();
+	r.player_start = player;
+	r.reset_player();
+	r.enemies.push(Enemy { centre_pos: enemy, prev_pos: enemy, .. Enemy::new(EnemyKind::Walker, 0.0) });
+	r
+}
+
+const ON_FLOOR: f64 = 0.075; //centre height of an enemy standing on the floor
+
+#[test]
+fn idle_then_patrol() {
+	let mut game_state = arena(vec![floor()], vec2(3.0, 0.1), vec2(0.0, ON_FLOOR));
+	let idle_ticks = game_state.enemy_params.idle_ticks;
+	while game_state.time < idle_ticks - 1.0 {
+		game_state.tick(1.0);
+		assert_eq!(game_state.enemies[0].ai, AiState::Idle, "at {}", game_state.time);
+	}
+	for _ in 0..5 {
+		game_state.tick(1.0);
+	}
+	assert_eq!(game_state.enemies[0].ai, AiState::Patrol);
+}
+
+#[test]
+fn patrol_turns_at_edges() { //up on a ledge with nothing underneath, so walking off would be obvious
+	let ledge = platform(vec2(0.0, -0.05), vec2(0.4, 0.1));
+	let mut game_state = arena(vec![ledge.clone(), platform(vec2(3.0, -0.05), vec2(1.0, 0.1))], vec2(3.0, 0.1), vec2(0.0, ON_FLOOR));
+	game_state.enemies[0].ai = AiState::Patrol;
+	game_state.enemies[0].ai_dir = 1.0;
+	let mut turns = 0;
+	let mut dir = 1.0;
+	for _ in 0..400 {
+		game_state.tick(1.0);
+		let e = &game_state.enemies[0];
+		assert!((e.centre_pos.y - ON_FLOOR).abs() < 1e-6, "fell off at {:?}", e.centre_pos);
+		assert!(e.centre_pos.x > ledge.pos().x && e.centre_pos.x < ledge.pos().x + ledge.size.x);
+		if e.ai_dir != dir {
+			dir = e.ai_dir;
+			turns += 1;
+		}
+	}
+	assert!(turns >= 2, "turned {} times", turns);
+}
+
+#[test]
+fn patrol_chases_in_sight() {
+	let mut game_state = arena(vec![floor()], vec2(1.0, 0.1), vec2(0.0, ON_FLOOR));
+	game_state.enemies[0].ai = AiState::Patrol;
+	game_state.tick(1.0);
+	assert_eq!(game_state.enemies[0].ai, AiState::Patrol); //out of sight_range
+	game_state.player.centre_pos.x = game_state.enemies[0].centre_pos.x + game_state.enemy_params.sight_range * 0.5;
+	game_state.tick(1.0);
+	assert_eq!(game_state.enemies[0].ai, AiState::Chase);
+	let start = game_state.enemies[0].centre_pos.x;
+	for _ in 0..30 {
+		game_state.tick(1.0);
+	}
+	assert!(game_state.enemies[0].centre_pos.x > start, "didn't go after the player");
+}
+
+#[test]
+fn jumps_up_to_ledge() { //ledge is one way so the jump can come up through it
+	let ledge = Platform { collision: Collision::OneWay, .. platform(vec2(0.4, 0.15), vec2(0.3, 0.1)) };
+	let mut game_state = arena(vec![floor(), ledge.clone()], vec2(0.45, 0.31), vec2(0.0, ON_FLOOR));
+	assert!(game_state.nav_for(EnemyKind::Walker).links.iter().flatten().any(|x| x.kind == LinkKind::Jump));
+	let (mut jumped, mut landed) = (false, false);
+	for _ in 0..300 {
+		game_state.tick(1.0);
+		let e = &game_state.enemies[0];
+		jumped |= e.ai == AiState::Jump;
+		let on_ledge = (e.centre_pos.y - (0.2 + ON_FLOOR)).abs() < 1e-3 && e.centre_pos.x > ledge.pos().x && e.centre_pos.x < ledge.pos().x + ledge.size.x;
+		landed |= jumped && on_ledge;
+	}
+	assert!(jumped, "never jumped");
+	assert!(landed, "never got up onto the ledge");
+}
+
+#[test]
+fn jumps_round_solid_ledge() { //starts right underneath, has to go out past the edge to get up since it can't come up through it
+	let ledge = platform(vec2(0.4, 0.25), vec2(0.3, 0.1));
+	let mut game_state = arena(vec![floor(), ledge.clone()], vec2(0.45, 0.51), vec2(0.4, ON_FLOOR));
+	assert!(game_state.nav_for(EnemyKind::Walker).links.iter().flatten().any(|x| x.kind == LinkKind::Jump));
+	let mut landed = false;
+	for _ in 0..300 {
+		game_state.tick(1.0);
+		let e = &game_state.enemies[0];
+		assert!(e.penetration(&ledge).is_none(), "went into the ledge at {:?}", e.centre_pos);
+		landed |= (e.centre_pos.y - (0.3 + ON_FLOOR)).abs() < 1e-3 && e.centre_pos.x > ledge.pos().x && e.centre_pos.x < ledge.pos().x + ledge.size.x;
+	}
+	assert!(landed, "never got up onto the ledge");
+}
+
+#[test]
+fn graph_fits_each_kind() { //a gap a shooter's wider body gets over but a walker's doesn't
+	let mut game_state = GameState::new();
+	game_state.platforms = vec![platform(vec2(-0.5, -0.05), vec2(1.0, 0.1)), platform(vec2(0.863, -0.05), vec2(1.0, 0.1))];
+	game_state.build_nav();
+	assert!(game_state.nav_for(EnemyKind::Walker).links[0].is_empty());
+	assert!(game_state.nav_for(EnemyKind::Shooter).links[0].iter().any(|x| x.to == 1 && x.kind == LinkKind::Jump));
+}
+
+#[test]
+fn flees_when_hurt() {
+	let mut game_state = arena(vec![floor()], vec2(0.3, 0.1), vec2(0.0, ON_FLOOR));
+	let max_hp = game_state.enemies[0].max_hp();
+	game_state.enemies[0].hp = max_hp * game_state.enemy_params.flee_hp * 0.5;
+	game_state.tick(1.0);
+	assert_eq!(game_state.enemies[0].ai, AiState::Flee);
+	for _ in 0..30 {
+		game_state.tick(1.0);
+	}
+	assert_eq!(game_state.enemies[0].ai, AiState::Flee);
+	assert!(game_state.enemies[0].centre_pos.x < -0.01, "didn't run away, at {:?}", game_state.enemies[0].centre_pos);
+}
@@ -0,0 +1,74 @@
+#![cfg(not(feature = "web"))]
+use seamstress::prelude::*;
+use std::time::Instant;
+
+fn world(rng: &mut RandGen) -> (Vec<Platform>, Vec<Enemy>) { //a few hundred platforms and a few dozen enemies scattered over a level sized area
+	let platforms = (0..400).map(|_| Platform {
+		centre_pos: vec2(rng.f64() * 20.0 - 10.0, rng.f64() * 10.0 - 5.0),
+		size: vec2(0.1 + rng.f64() * 0.5, 0.05 + rng.f64() * 0.2),
+		.. Default::default()
+	}).collect();
+	let enemies = (0..40).map(|_| Enemy {
+		centre_pos: vec2(rng.f64() * 20.0 - 10.0, rng.f64() * 10.0 - 5.0),
+		.. Enemy::new(EnemyKind::Walker, 0.0)
+	}).collect();
+	(platforms, enemies)
+}
+
+fn movers(rng: &mut RandGen) -> Vec<(AABB, Vec2<f64>)> {
+	(0..1000).map(|_| (
+		AABB { pos: vec2(rng.f64() * 20.0 - 10.0, rng.f64() * 10.0 - 5.0), size: vec2(0.1, 0.2), vel: Vec2::zero() },
+		vec2(rng.f64() * 0.26 - 0.13, rng.f64() * 0.26 - 0.13),
+	)).collect()
+}
+
+fn full_scan<'a>(platforms: &'a [Platform], enemies: &'a [Enemy]) -> Vec<(bool, &'a dyn BoundingBox)> {
+	enemies.iter().map(|x| (true, x as &dyn BoundingBox)).chain(platforms.iter().map(|x| (false, x as &dyn BoundingBox))).collect()
+}
+
+fn contacts(mover: &AABB, movement: Vec2<f64>, objects: Vec<(bool, &dyn BoundingBox)>) -> Vec<(Sweep, f64, bool)> {
+	sweep_all(mover, movement, objects.into_iter()).into_iter().map(|c| (c.sweep, c.surface, c.is_enemy)).collect()
+}
+
+#[test]
+fn nearby_matches_full_scan() { //the broadphase is only allowed to make things faster, never to change what gets hit
+	let mut rng = RandGen::new(1);
+	let (platforms, enemies) = world(&mut rng);
+	let mut broadphase = Broadphase::default();
+	broadphase.rebuild(&platforms, &enemies);
+	for (mover, movement) in movers(&mut rng) {
+		assert_eq!(
+			contacts(&mover, movement, broadphase.nearby(&platforms, &enemies, &mover, movement, None)),
+			contacts(&mover, movement, full_scan(&platforms, &enemies)),
+		);
+	}
+}
+
+#[test]
+#[ignore]
+fn nearby_is_faster_than_full_scan() {
+	let mut rng = RandGen::new(2);
+	let (platforms, enemies) = world(&mut rng);
+	let movers = movers(&mut rng);
+	let mut broadphase = Broadphase::default();
+	let start = Instant::now();
+	let mut hits = 0;
+	for _ in 0..20 {
+		broadphase.rebuild(&platforms, &enemies); //rebuilding is part of the cost, it happens every tick
+		for &(mover, movement) in movers.iter() {
+			hits += contacts(&mover, movement, broadphase.nearby(&platforms, &enemies, &mover, movement, None)).len();
+		}
+	}
+	let hashed = start.elapsed();
+	let start = Instant::now();
+	let mut scan_hits = 0;
+	for _ in 0..20 {
+		for &(mover, movement) in movers.iter() {
+			scan_hits += contacts(&mover, movement, full_scan(&platforms, &enemies)).len();
+		}
+	}
+	let scanned = start.elapsed();
+	println!("broadphase {:?}, full scan {:?}, {:.1}x faster", hashed, scanned, scanned.as_secs_f64() / hashed.as_secs_f64());
+	assert_eq!(hits, scan_hits);
+	assert!(hashed < scanned);
+}
@@ -0,0 +1,160 @@
+#![cfg(not(feature = "web"))]
+mod common;
+use common::*;
+use seamstress::prelude::*;
+
+#[test]
+fn fast_fall_hits_thin_platform() { //moves further in one tick than the platform is thick, so checking the end position alone would miss it
+	let s = sweep_aabb(vec2(-0.05, 0.05), vec2(0.1, 0.2), vec2(0.0, -0.13), vec2(-0.5, -0.1), vec2(1.0, 0.1)).expect("should hit");
+	assert!(close(s.time, 0.05 / 0.13), "time {}", s.time);
+	assert!(close(s.normal.x, 0.0) && close(s.normal.y, 1.0), "normal {:?}", s.normal);
+}
+
+#[test]
+fn fast_fall_misses_when_not_under() {
+	assert!(sweep_aabb(vec2(0.6, 0.05), vec2(0.1, 0.2), vec2(0.0, -0.13), vec2(-0.5, -0.1), vec2(1.0, 0.1)).is_none());
+}
+
+#[test]
+fn corner_hit_counts_as_landing() { //entry_x == entry_y, ties go to the vertical face so we land rather than stop dead
+	let s = sweep_aabb(vec2(0.0, 0.2), vec2(0.1, 0.1), vec2(0.2, -0.2), vec2(0.2, -0.5), vec2(0.5, 0.6)).expect("should hit");
+	assert!(close(s.time, 0.5), "time {}", s.time);
+	assert!(close(s.normal.x, 0.0) && close(s.normal.y, 1.0), "normal {:?}", s.normal);
+}
+
+#[test]
+fn rising_platform_catches_still_mover() { //only the platform moves, so the hit comes from its velocity
+	let p = Platform { vel: vec2(0.0, 0.05), .. platform(vec2(0.0, -0.05), vec2(1.0, 0.1)) };
+	let mover = AABB { pos: vec2(-0.05, 0.03), size: vec2(0.1, 0.2), vel: Vec2::zero() };
+	let contacts = sweep_all(&mover, Vec2::zero(), std::iter::once((false, &p as &dyn BoundingBox)));
+	assert_eq!(contacts.len(), 1);
+	let c = contacts[0];
+	assert!(close(c.sweep.time, 0.6), "time {}", c.sweep.time);
+	assert!(close(c.sweep.normal.y, 1.0), "normal {:?}", c.sweep.normal);
+	assert!(close(c.surface, 0.0), "surface {}", c.surface);
+	assert!(close(c.vel.y, 0.05));
+}
+
+#[test]
+fn sliding_along_floor_doesnt_catch() { //walking across the seam between two floor pieces, and along the top of one
+	let a = platform(vec2(-0.5, -0.05), vec2(1.0, 0.1));
+	let b = platform(vec2(0.5, -0.05), vec2(1.0, 0.1));
+	let mover = AABB { pos: vec2(-0.15, 0.0), size: vec2(0.1, 0.2), vel: Vec2::zero() };
+	let objects = vec![(false, &a as &dyn BoundingBox), (false, &b as &dyn BoundingBox)];
+	assert!(sweep_all(&mover, vec2(0.2, 0.0), objects.iter().copied()).is_empty());
+	assert!(sweep_all(&mover, vec2(-0.2, 0.0), objects.iter().copied()).is_empty());
+}
+
+#[test]
+fn sliding_along_wall_doesnt_catch() {
+	let wall = platform(vec2(0.05, 0.0), vec2(0.1, 2.0));
+	let mover = AABB { pos: vec2(-0.1, 0.0), size: vec2(0.1, 0.2), vel: Vec2::zero() };
+	assert!(sweep_all(&mover, vec2(0.0, -0.13), std::iter::once((false, &wall as &dyn BoundingBox))).is_empty());
+}
+
+fn thin_ceiling() -> Platform { //0.05 thick, sloping up to the right, underside is y = 0.25 + 0.1x
+	Platform::from_polygon(vec![vec2(-0.5, 0.2), vec2(0.5, 0.3), vec2(0.5, 0.35), vec2(-0.5, 0.25)])
+}
+
+#[test]
+fn jump_into_angled_ceiling_stops_at_underside() { //the box's top left corner is first to touch, at x -0.05 where the underside is at 0.245
+	let ceiling = thin_ceiling();
+	let mover = AABB { pos: vec2(-0.05, -0.05), size: vec2(0.1, 0.2), vel: Vec2::zero() };
+	let contacts = sweep_all(&mover, vec2(0.0, 0.13), std::iter::once((false, &ceiling as &dyn BoundingBox)));
+	assert_eq!(contacts.len(), 1);
+	let c = contacts[0];
+	assert!(close(c.sweep.time, 0.095 / 0.13), "time {}", c.sweep.time);
+	assert!(close(c.sweep.normal.y, -1.0), "normal {:?}", c.sweep.normal);
+	assert!(close(c.surface, 0.245), "surface {}", c.surface);
+	let s = sweep_polygon(mover.pos, mover.size, vec2(0.0, 0.13), &ceiling.polygon().unwrap()).unwrap();
+	assert!(s.normal.y < -0.9 && s.normal.x > 0.0, "face normal {:?}", s.normal); //the slanted underside, not the box's own axis
+}
+
+#[test]
+fn jump_into_angled_ceiling_doesnt_tunnel() { //depenetrating alone would push us out of the top, it's thinner there than how far we got in
+	let mut game_state = GameState::new();
+	game_state.platforms.push(thin_ceiling());
+	game_state.player = Player::spawn(vec2(0.0, 0.05));
+	game_state.player.vel = vec2(0.0, 0.13);
+	game_state.tick(1.0);
+	let p = &game_state.player;
+	assert!(p.centre_pos.y + p.size().y / 2.0 <= 0.245 + 1e-6, "player top at {}", p.centre_pos.y + p.size().y / 2.0);
+	assert!(p.vel.y <= 0.0);
+}
+
+#[test]
+fn walking_up_slope_isnt_blocked() { //bottom right corner is on the slope, slopes are left to stand_on_slopes when moving sideways
+	let slope = Platform::from_polygon(vec![vec2(0.0, 0.0), vec2(1.0, 0.0), vec2(1.0, 0.5)]);
+	let mover = AABB { pos: vec2(0.3, 0.2), size: vec2(0.1, 0.2), vel: Vec2::zero() };
+	assert!(sweep_all(&mover, vec2(0.05, 0.0), std::iter::once((false, &slope as &dyn BoundingBox))).is_empty());
+}
+
+fn steep_wedge() -> Platform { //left face goes from (0, -1) up to (0.3, 1), far too steep to stand on
+	Platform::from_polygon(vec![vec2(0.0, -1.0), vec2(0.3, -1.0), vec2(0.3, 1.0)])
+}
+
+#[test]
+fn landing_on_steep_slope_isnt_ground() { //hit while moving down, so the contact's along y, but the face it hit is still steep
+	let wedge = steep_wedge();
+	let mover = AABB { pos: vec2(0.05, 0.05), size: vec2(0.1, 0.2), vel: Vec2::zero() };
+	let contacts = sweep_all(&mover, vec2(0.0, -0.1), std::iter::once((false, &wedge as &dyn BoundingBox)));
+	assert_eq!(contacts.len(), 1);
+	let c = contacts[0];
+	assert!(close(c.sweep.normal.y, 1.0), "normal {:?}", c.sweep.normal);
+	assert!(c.face.x < 0.0 && c.face.y > 0.0, "face {:?}", c.face);
+	assert!(c.steep() && !c.walkable());
+	let slid = c.slide(vec2(0.0, -0.1));
+	assert!(slid.x < 0.0 && slid.y < 0.0, "should slide down and away, got {:?}", slid);
+	assert!(dot(slid, c.face).abs() < 1e-9);
+}
+
+#[test]
+fn dropped_on_steep_slope_slides_off() {
+	let mut game_state = GameState::new();
+	game_state.platforms.push(steep_wedge());
+	game_state.player = Player::spawn(vec2(0.1, 1.3)); //bottom right corner comes down on the face at (0.15, 0)
+	for _ in 0..300 {
+		game_state.tick(1.0);
+		assert_eq!(game_state.player.grounded, 0.0, "stood on the slope at {:?}", game_state.player.centre_pos);
+	}
+	let p = &game_state.player;
+	assert!(p.centre_pos.x < 0.0 && p.centre_pos.y < -1.2, "didn't slide off, ended at {:?}", p.centre_pos);
+}
+
+fn unit_box() -> AABB { AABB { pos: vec2(0.0, 0.0), size: vec2(0.1, 0.1), vel: Vec2::zero() } }
+
+#[test]
+fn segment_through_the_middle() {
+	assert_eq!(segment_aabb_distance(vec2(-0.1, 0.05), vec2(0.2, 0.05), vec2(0.0, 0.0), vec2(0.1, 0.1)), 0.0);
+	assert_eq!(segment_aabb_distance(vec2(-0.1, -0.1), vec2(0.2, 0.2), vec2(0.0, 0.0), vec2(0.1, 0.1)), 0.0);
+	assert_eq!(segment_aabb_distance(vec2(0.05, 0.3), vec2(0.05, -0.3), vec2(0.0, 0.0), vec2(0.1, 0.1)), 0.0);
+}
+
+#[test]
+fn segment_grazing() { //along an edge counts as touching, past a corner is measured to the corner
+	assert!(close(segment_aabb_distance(vec2(-0.1, 0.1), vec2(0.2, 0.1), vec2(0.0, 0.0), vec2(0.1, 0.1)), 0.0));
+	let d = segment_aabb_distance(vec2(0.05, 0.2), vec2(0.2, 0.05), vec2(0.0, 0.0), vec2(0.1, 0.1));
+	assert!(close(d, 0.05 / 2f64.sqrt()), "distance {}", d);
+}
+
+#[test]
+fn segment_endpoints() { //stopping short is measured from the end, ending inside touches
+	assert!(close(segment_aabb_distance(vec2(-0.3, 0.05), vec2(-0.02, 0.05), vec2(0.0, 0.0), vec2(0.1, 0.1)), 0.02));
+	assert!(close(segment_aabb_distance(vec2(0.13, 0.14), vec2(0.3, 0.3), vec2(0.0, 0.0), vec2(0.1, 0.1)), 0.05));
+	assert_eq!(segment_aabb_distance(vec2(0.05, 0.05), vec2(0.3, 0.3), vec2(0.0, 0.0), vec2(0.1, 0.1)), 0.0);
+	assert_eq!(segment_aabb_distance(vec2(0.02, 0.02), vec2(0.08, 0.07), vec2(0.0, 0.0), vec2(0.1, 0.1)), 0.0);
+	assert!(close(segment_aabb_distance(vec2(0.05, -0.03), vec2(0.05, -0.03), vec2(0.0, 0.0), vec2(0.1, 0.1)), 0.03)); //zero length, just a point
+}
+
+#[test]
+fn thread_is_a_capsule() { //misses by less than its width still hits
+	let b = unit_box();
+	let t = |from: Vec2<f64>, to: Vec2<f64>| Thread::new(from, to, 0.0, -1.0);
+	assert!(t(vec2(-0.1, 0.0), vec2(0.2, 0.0)).width() == 0.01);
+	assert!(t(vec2(-0.1, 0.105), vec2(0.2, 0.105)).collides(&b)); //skims over the top
+	assert!(!t(vec2(-0.1, 0.115), vec2(0.2, 0.115)).collides(&b));
+	assert!(t(vec2(-0.3, 0.05), vec2(-0.008, 0.05)).collides(&b)); //stops just short
+	assert!(!t(vec2(-0.3, 0.05), vec2(-0.012, 0.05)).collides(&b));
+	assert!(t(vec2(0.1 + 0.007, -0.1), vec2(0.1 + 0.007, 0.3)).collides(&b)); //passes down the side
+	assert!(t(vec2(-0.1, 0.05), vec2(0.2, 0.05)).collides(&b));
+}
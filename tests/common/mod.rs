@@ -0,0 +1,11 @@
+//fixtures shared between the integration tests, not every test uses all of them
+#![allow(dead_code)]
+use seamstress::prelude::*;
+
+pub fn close(a: f64, b: f64) -> bool { (a - b).abs() < 1e-9 }
+
+pub fn platform(centre_pos: Vec2<f64>, size: Vec2<f64>) -> Platform {
+	Platform { centre_pos, size, .. Default::default() }
+}
+
+pub fn floor() -> Platform { platform(vec2(0.0, -0.05), vec2(10.0, 0.1)) } //top at 0
@@ -0,0 +1,64 @@
+#![cfg(not(feature = "web"))]
+use seamstress::prelude::*;
+
+fn level() -> GameState {
+	let mut r = parse_level(FIRST_LEVEL).unwrap();
+	r.seed(7);
+	r
+}
+
+#[test]
+fn difference_names_the_field() {
+	let mut a = level();
+	a.player.vel.x = 0.25;
+	let mut b = a.clone();
+	assert_eq!(first_difference(&a, &b), None);
+	b.player.vel.x = 0.5;
+	assert_eq!(first_difference(&a, &b), Some("game_state.player.vel.x: 0.25 vs 0.5".to_string()));
+	let mut c = a.clone();
+	c.platforms[2].size.y = 2.0;
+	assert_eq!(first_difference(&a, &c), Some("game_state.platforms[2].size.y: 1.0 vs 2.0".to_string()));
+	assert_eq!(diff_saves(&save_game(&a), &save_game(&c)), Ok(Some("game_state.platforms[2].size.y: 1.0 vs 2.0".to_string()))); //same thing after going through a save
+}
+
+#[test]
+fn difference_in_length_shows_as_length() { //rather than as whatever came after the missing element
+	let a = level();
+	let mut b = a.clone();
+	b.platforms.pop();
+	assert_eq!(first_difference(&a, &b), Some(format!("game_state.platforms.len: {} vs {}", a.platforms.len(), b.platforms.len())));
+}
+
+fn run(diverge_at: Option<u64>) -> HashStream { //HASH_INTERVAL is 30, so hashes at 0, 30, 60...
+	let mut game_state = level();
+	let mut r = HashStream::default();
+	for t in 0..=150 {
+		if Some(t) == diverge_at {
+			game_state.player.vel.x += 0.01;
+		}
+		game_state.tick(1.0);
+		r.record(t, &game_state);
+	}
+	r
+}
+
+#[test]
+fn streams_find_where_they_split() {
+	let (a, b) = (run(None), run(Some(70)));
+	assert_eq!(a.hashes.keys().copied().collect::<Vec<_>>(), vec![0, 30, 60, 90, 120, 150]);
+	assert_eq!(a.first_divergence(&b), Some(90));
+	assert_eq!(a.last_agreement(&b), Some(60));
+	assert_eq!(a.first_divergence(&run(None)), None);
+	assert_eq!(a.last_agreement(&run(None)), Some(150));
+}
+
+#[test]
+fn streams_only_compare_ticks_both_hashed() { //one side missing a tick can't count as agreeing or not
+	let (a, mut b) = (run(None), run(Some(100)));
+	b.hashes.remove(&60);
+	b.hashes.remove(&120);
+	assert_eq!(a.first_divergence(&b), Some(150));
+	assert_eq!(a.last_agreement(&b), Some(90));
+	b.hashes.remove(&90);
+	assert_eq!(a.last_agreement(&b), Some(30));
+}
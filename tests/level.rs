@@ -0,0 +1,39 @@
+#![cfg(not(feature = "web"))]
+use seamstress::prelude::*;
+
+fn error(src: &str) -> String {
+	parse_level(src).unwrap_err().to_string()
+}
+
+#[test]
+fn bad_token() {
+	assert_eq!(error("start 0 0\nplatform 0 zero 1 1"), "line 2, column 12: expected a number for platform centre y, found \"zero\"");
+	assert_eq!(error("start 0 0\n\n\tplatfrom 0 0 1 1"), "line 3, column 2: unknown keyword \"platfrom\"");
+	assert_eq!(error("start 0 0\nplatform 0 0 1 1 one_way sideways"), "line 2, column 26: unexpected \"sideways\"");
+}
+
+#[test]
+fn missing_argument() { //pointed at just past the end of the line, comments don't count
+	assert_eq!(error("start 0 0\nplatform 0 0 1"), "line 2, column 15: missing platform height");
+	assert_eq!(error("start 0 0\nhazard 0 0 1 1   # no damage"), "line 2, column 15: missing hazard damage");
+}
+
+#[test]
+fn stray_waypoint() {
+	assert_eq!(error("start 0 0\nplatform 0 0 1 1\n  waypoint 1 1 60"), "line 3, column 3: waypoint has to come after a path");
+	assert_eq!(error("start 0 0\nplatform 0 0 1 1\npath loop\nwaypoint 0 0 60\nwaypoint 1 0 60\nplatform 3 0 1 1\nwaypoint 1 1 60"), "line 7, column 1: waypoint has to come after a path"); //the path ended at the next platform
+}
+
+#[test]
+fn negative_hazard_damage() {
+	assert_eq!(error("start 0 0\nhazard 0 0 1 1 -2"), "line 2, column 16: hazard damage can't be less than 0, that would heal");
+	assert_eq!(parse_level("start 0 0\nhazard 0 0 1 1 0").unwrap().hazards[0].damage, 0.0);
+}
+
+#[test]
+fn waypoint_easing() {
+	let game_state = parse_level("start 0 0\nplatform 0 0 1 1\npath loop\nwaypoint 0 0 60 smooth\nwaypoint 1 0 60 linear\nwaypoint 1 1 60").unwrap();
+	let easings = game_state.platforms[0].path.as_ref().unwrap().waypoints.iter().map(|x| x.easing).collect::<Vec<_>>();
+	assert_eq!(easings, vec![Easing::Smooth, Easing::Linear, Easing::Linear]);
+	assert_eq!(error("start 0 0\nplatform 0 0 1 1\npath loop\nwaypoint 0 0 60 fast"), "line 4, column 17: expected smooth, linear or nothing, found \"fast\"");
+}
@@ -0,0 +1,95 @@
+#![cfg(not(feature = "web"))]
+//two rollback peers in one process, with the relay swapped for queues that hold messages back
+use seamstress::prelude::*;
+use std::collections::VecDeque;
+use InputEvent::*;
+
+const TICKS: u64 = 600;
+const SEED: u64 = 4242;
+
+struct Peer {
+	game_state: GameState,
+	rollback: Rollback,
+	outbox: VecDeque<(u64, String)>, //messages on their way to the other peer, with the frame they turn up on
+}
+
+impl Peer {
+	fn new(local: usize) -> Self { //same as when the relay says hello
+		let mut game_state = parse_level(FIRST_LEVEL).unwrap();
+		game_state.add_coop_player();
+		game_state.seed(SEED);
+		game_state.follow = local;
+		Self { game_state, rollback: Rollback::new(local, 1.0), outbox: VecDeque::new() }
+	}
+	
+	fn advance(&mut self, frame: u64, latency: u64) {
+		let t = self.rollback.tick as usize;
+		let events = script(t + self.rollback.local * 50); //so the two players don't do the same thing
+		let msg = self.rollback.advance(&mut self.game_state, events);
+		let at = self.outbox.back().map(|x| x.0).unwrap_or(0).max(frame + latency); //late, but still in order like a websocket
+		self.outbox.push_back((at, msg));
+	}
+	
+	fn deliver(&mut self, other: &mut Peer, frame: u64) {
+		while other.outbox.front().map(|x| x.0 <= frame).unwrap_or(false) {
+			let (_, msg) = other.outbox.pop_front().unwrap();
+			self.rollback.receive(&mut self.game_state, &msg).unwrap();
+		}
+	}
+}
+
+fn script(t: usize) -> Vec<InputEvent> {
+	let key = |k: &str| k.to_string();
+	match t % 200 {
+		10 => vec![KeyDown(key("d"))],
+		40 => vec![KeyDown(key("w"))],
+		47 => vec![KeyUp(key("w"))],
+		90 => vec![KeyUp(key("d")), KeyDown(key("a"))],
+		120 => vec![LeftClick(vec2(0.3, 0.5))],
+		150 => vec![KeyUp(key("a")), RightClick],
+		_ => Vec::new(),
+	}
+}
+
+fn play() -> (Peer, Peer) {
+	let (mut a, mut b) = (Peer::new(0), Peer::new(1));
+	let mut frame = 0;
+	while a.rollback.tick < TICKS || b.rollback.tick < TICKS {
+		a.deliver(&mut b, frame);
+		b.deliver(&mut a, frame);
+		if a.rollback.tick < TICKS && a.rollback.can_advance() {
+			a.advance(frame, frame * 7 % 13);
+		}
+		if frame % 3 != 0 && b.rollback.tick < TICKS && b.rollback.can_advance() { //b runs slower, so a gets ahead and has to wait
+			b.advance(frame, frame * 5 % 11);
+		}
+		frame += 1;
+		assert!(frame < 10 * TICKS, "stuck at ticks {} and {}", a.rollback.tick, b.rollback.tick);
+	}
+	a.deliver(&mut b, u64::MAX);
+	b.deliver(&mut a, u64::MAX);
+	(a, b)
+}
+
+#[test]
+fn late_messages_end_up_identical() {
+	let (a, b) = play();
+	assert!(a.rollback.rollbacks > 0, "nothing got rolled back, the test isn't testing anything"); //a's the one running ahead
+	assert_eq!(first_difference(&a.game_state, &b.game_state), None);
+	assert_eq!(state_hash(&a.game_state), state_hash(&b.game_state));
+	assert_eq!((a.rollback.desync, b.rollback.desync), (None, None));
+}
+
+#[test]
+fn duplicate_and_old_ticks_are_refused() {
+	let (mut a, mut b) = (Peer::new(0), Peer::new(1));
+	for frame in 0..3 {
+		b.advance(frame, 0);
+	}
+	let sent = b.outbox.iter().map(|x| x.1.clone()).collect::<Vec<_>>();
+	a.deliver(&mut b, 2);
+	let before = state_hash(&a.game_state);
+	assert_eq!(a.rollback.receive(&mut a.game_state, &sent[2]), Err("got tick 2 twice".to_string()));
+	assert_eq!(a.rollback.receive(&mut a.game_state, &sent[0]), Err("got tick 0 twice".to_string()));
+	assert_eq!(state_hash(&a.game_state), before);
+}
@@ -0,0 +1,77 @@
+#![cfg(not(feature = "web"))]
+mod common;
+use common::*;
+use seamstress::prelude::*;
+
+const START: Vec2<f64> = Vec2{ x: 3.0, y: 1.0, }; //well away from everything, so a respawn is obvious
+
+fn lift(from: Vec2<f64>, to: Vec2<f64>, size: Vec2<f64>) -> Platform { //goes from to to and back, 60 ticks each way
+	let waypoint = |pos| Waypoint { pos, duration: 60.0, easing: Easing::Linear };
+	Platform { path: Some(PlatformPath::new(vec![waypoint(from), waypoint(to)], PathMode::PingPong)), .. platform(from, size) }
+}
+
+fn level(platforms: Vec<Platform>, player: Vec2<f64>, enemy: Vec2<f64>) -> GameState {
+	let mut r = GameState::new();
+	r.platforms = platforms;
+	r.platforms.push(platform(START - vec2(0.0, 0.15), vec2(0.5, 0.1))); //somewhere to land after respawning
+	r.player_start = START;
+	r.player = Player::spawn(player);
+	let mut e = Enemy { centre_pos: enemy, prev_pos: enemy, .. Enemy::new(EnemyKind::Walker, 0.0) };
+	e.stitch(1.0, f64::INFINITY); //so it stands still instead of going for the player
+	r.enemies.push(e);
+	r
+}
+
+#[test]
+fn lift_crushes_riders_against_ceiling() { //lift top starts at 0 and rises, ceiling underside is at 0.35
+	let ceiling = platform(vec2(0.0, 0.4), vec2(2.0, 0.1));
+	let mut game_state = level(vec![lift(vec2(0.0, -0.05), vec2(0.0, 0.45), vec2(0.6, 0.1)), ceiling], vec2(-0.2, 0.1), vec2(0.2, 0.075));
+	for _ in 0..5 { //carried up with it first
+		game_state.tick(1.0);
+	}
+	assert!(game_state.player.centre_pos.y > 0.1 && game_state.player.centre_pos.x < 0.0, "player wasn't carried: {:?}", game_state.player.centre_pos);
+	assert!(game_state.enemies.len() == 1 && game_state.enemies[0].centre_pos.y > 0.075, "enemy wasn't carried");
+	for _ in 0..30 {
+		game_state.tick(1.0);
+	}
+	assert!((game_state.player.centre_pos - START).magnitude() < 0.5, "player wasn't crushed: {:?}", game_state.player.centre_pos);
+	assert!(game_state.enemies.is_empty(), "enemy wasn't crushed");
+}
+
+#[test]
+fn lift_leaves_others_alone() { //wedged between two walls, so getting pushed out of one puts you into the other, but the lift is nowhere near
+	let walls = vec![platform(vec2(-0.06, 0.5), vec2(0.1, 1.0)), platform(vec2(0.06, 0.5), vec2(0.1, 1.0))];
+	let mut platforms = vec![lift(vec2(-3.0, -0.05), vec2(-3.0, 0.45), vec2(0.6, 0.1))];
+	platforms.extend(walls);
+	let mut game_state = level(platforms, vec2(0.0, 0.8), vec2(0.0, 0.3));
+	game_state.tick(1.0);
+	assert!((game_state.player.centre_pos - START).magnitude() > 1.0, "player got crushed");
+	assert_eq!(game_state.enemies.len(), 1, "enemy got crushed");
+}
+
+#[test]
+fn bridge_keeps_older_thread() { //thread laid right to left across three platforms, sagging into both gaps
+	let mut game_state = level(vec![
+		platform(vec2(1.0, -0.05), vec2(1.0, 0.1)),
+		platform(vec2(0.0, -0.05), vec2(0.4, 0.1)),
+		platform(vec2(-1.0, -0.05), vec2(1.0, 0.1)),
+	], vec2(1.2, 0.1), START);
+	let nodes = [vec2(0.9, 0.005), vec2(0.6, 0.005), vec2(0.35, -0.2), vec2(0.1, 0.005), vec2(-0.1, 0.005), vec2(-0.35, -0.2), vec2(-0.6, 0.005), vec2(-0.9, 0.005)]; //newest first
+	let mut to = game_state.player.centre_pos;
+	game_state.player.thread = nodes.iter().map(|&from| {
+		let t = Thread::new(from, to, 0.0, -1.0);
+		to = from;
+		t
+	}).collect();
+	let platforms = game_state.platforms.len();
+	game_state.input_event(InputEvent::KeyDown("e".to_string()));
+	assert_eq!(game_state.platforms.len(), platforms + 1, "no bridge over the first gap");
+	let froms = |g: &GameState| g.player.thread.iter().map(|x| x.from).collect::<Vec<_>>();
+	assert_eq!(froms(&game_state), vec![nodes[0], nodes[1], nodes[3], nodes[4], nodes[5], nodes[6], nodes[7]]); //only the sag is gone
+	let thread = &game_state.player.thread;
+	assert!((1..thread.len()).all(|i| thread[i].to == thread[i-1].from), "thread came apart");
+	assert!(close(thread[2].length, 0.5));
+	game_state.input_event(InputEvent::KeyDown("e".to_string())); //and the older gap can still be bridged after
+	assert_eq!(game_state.platforms.len(), platforms + 2, "no bridge over the second gap");
+	assert_eq!(froms(&game_state), vec![nodes[0], nodes[1], nodes[3], nodes[4], nodes[6], nodes[7]]);
+}
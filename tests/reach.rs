@@ -0,0 +1,45 @@
+#![cfg(not(feature = "web"))]
+mod common;
+use common::*;
+use seamstress::prelude::*;
+
+fn room(sealed: bool) -> GameState { //a small room sat on the floor to the right of the start, with the goal inside it
+	let mut r = GameState::new();
+	r.platforms = vec![
+		platform(vec2(0.0, -0.8), vec2(20.0, 0.4)),
+		platform(vec2(1.0, -0.45), vec2(0.6, 0.1)), //room floor, well above the floor
+		platform(vec2(0.65, -0.25), vec2(0.1, 0.7)),
+	];
+	if sealed {
+		r.platforms.push(platform(vec2(1.35, -0.25), vec2(0.1, 0.7)));
+		r.platforms.push(platform(vec2(1.0, 0.15), vec2(0.8, 0.1)));
+	}
+	r.player_start = Vec2::zero();
+	r.player = Player::spawn(r.player_start);
+	r.goal = Some(Goal { centre_pos: vec2(1.0, -0.25), size: vec2(0.1, 0.2) });
+	r
+}
+
+#[test]
+fn sealed_goal_is_unreachable() { //close enough to jump into if the walls weren't there
+	let r = reachability(&room(true)).unwrap();
+	assert!(!r.reachable[1], "got into the room through its walls");
+	assert_eq!(r.goal, Some(false));
+}
+
+#[test]
+fn open_goal_is_reachable() { //same room with the right wall and roof taken off
+	let r = reachability(&room(false)).unwrap();
+	assert!(r.reachable[1], "couldn't get into the room");
+	assert_eq!(r.goal, Some(true));
+}
+
+#[test]
+fn first_level_can_be_played() {
+	let mut game_state = parse_level(FIRST_LEVEL).unwrap();
+	let r = reachability(&game_state).unwrap();
+	assert_eq!(r.unreachable(), vec![1]); //the left pillar is a few cm higher than a jump gets, it's only scenery
+	assert_eq!(r.goal, None);
+	game_state.goal = Some(Goal { centre_pos: vec2(0.5, 0.75), size: vec2(0.1, 0.2) }); //on the top platform
+	assert_eq!(reachability(&game_state).unwrap().goal, Some(true));
+}
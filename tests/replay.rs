@@ -0,0 +1,62 @@
+#![cfg(not(feature = "web"))]
+use seamstress::prelude::*;
+use InputEvent::*;
+
+const TICKS: usize = 1200; //long enough for spawners to go off a few times and a detonation to finish
+
+fn script(t: usize) -> Vec<InputEvent> { //walks back and forth, jumps, grapples and blows up the thread
+	let key = |k: &str| k.to_string();
+	match t {
+		10 => vec![KeyDown(key("d"))],
+		60 => vec![KeyDown(key("w"))],
+		70 => vec![KeyUp(key("w"))],
+		200 => vec![KeyUp(key("d")), KeyDown(key("a"))],
+		300 => vec![LeftClick(vec2(0.0, 0.6))],
+		340 => vec![KeyDown(key("r"))],
+		400 => vec![KeyUp(key("r")), LeftClick(vec2(0.0, 0.0))],
+		500 => vec![KeyUp(key("a")), KeyDown(key("x"))],
+		501 => vec![KeyUp(key("x"))],
+		800 => vec![RightClick, KeyDown(key("s"))],
+		850 => vec![KeyUp(key("s"))],
+		_ => Vec::new(),
+	}
+}
+
+fn record(seed: u64) -> (GameState, Recording) {
+	let mut game_state = parse_level(FIRST_LEVEL).unwrap();
+	let mut recorder = Recorder::new(&mut game_state, seed, 1.0);
+	for t in 0..TICKS {
+		recorder.tick(&mut game_state, script(t));
+	}
+	(game_state, recorder.recording)
+}
+
+#[test]
+fn replay_ends_bit_identical() {
+	let (recorded, recording) = record(12345);
+	let mut replayed = parse_level(FIRST_LEVEL).unwrap();
+	assert_eq!(check_replay(&mut replayed, &recording), Ok(()));
+	assert_eq!(replayed.time, recorded.time);
+	assert_eq!(first_difference(&recorded, &replayed), None);
+	assert_eq!(state_hash(&recorded), state_hash(&replayed));
+	assert!(save_game(&recorded) == save_game(&replayed));
+}
+
+#[test]
+fn replay_survives_being_written_out() { //the text format has to keep every event exactly, click positions included
+	let (recorded, recording) = record(777);
+	let parsed = Recording::parse(&recording.to_string()).unwrap();
+	let strings = |r: &Recording| r.inputs.iter().map(|x| x.iter().map(event_to_string).collect::<Vec<_>>()).collect::<Vec<_>>();
+	assert_eq!(strings(&parsed), strings(&recording));
+	let mut replayed = parse_level(FIRST_LEVEL).unwrap();
+	assert_eq!(check_replay(&mut replayed, &parsed), Ok(()));
+	assert_eq!(state_hash(&recorded), state_hash(&replayed));
+}
+
+#[test]
+fn replay_with_wrong_seed_desyncs() { //otherwise the checks above could pass by not checking anything
+	let (_, mut recording) = record(12345);
+	recording.seed += 1;
+	let mut replayed = parse_level(FIRST_LEVEL).unwrap();
+	assert!(check_replay(&mut replayed, &recording).is_err());
+}
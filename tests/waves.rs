@@ -0,0 +1,87 @@
+#![cfg(not(feature = "web"))]
+use seamstress::prelude::*;
+
+const LEVEL: &str = include_str!("levels/waves.level");
+
+fn level(seed: u64) -> GameState {
+	let mut r = parse_level(LEVEL).unwrap();
+	r.seed(seed);
+	r
+}
+
+fn timeline(events: &[SpawnEvent]) -> Vec<(f64, usize)> {
+	events.iter().map(|x| (x.time, x.spawner)).collect()
+}
+
+#[test]
+fn fixed_seed_fixed_timeline() {
+	let events = spawn_schedule(&level(42), 600.0);
+	let expected = [
+		(100.0, 0), (150.0, 1), (193.0, 0), (200.0, 2), (292.0, 0), (297.0, 1), //each spawner's first spawn comes exactly one interval in
+		(399.0, 0), (424.0, 2), (472.0, 1), (494.0, 0), (568.0, 0), (577.0, 1), (592.0, 2), //second wave, intervals 0.8 as long
+	];
+	assert_eq!(timeline(&events), expected.to_vec());
+	let kinds = [EnemyKind::Walker, EnemyKind::Flyer, EnemyKind::Shooter];
+	for e in events.iter() {
+		assert_eq!(e.kind, kinds[e.spawner]);
+		assert!((e.toughness - if e.time < 300.0 { 1.0 } else { 1.2 }).abs() < 1e-9, "toughness {} at {}", e.toughness, e.time);
+	}
+	assert_eq!(events, spawn_schedule(&level(42), 600.0));
+}
+
+#[test]
+fn different_seed_different_timeline() {
+	assert_ne!(timeline(&spawn_schedule(&level(42), 2000.0)), timeline(&spawn_schedule(&level(43), 2000.0)));
+	assert_ne!(timeline(&spawn_schedule(&level(42), 2000.0)), timeline(&spawn_schedule(&level(9001), 2000.0)));
+}
+
+#[test]
+fn schedule_matches_tick() { //caps are raised out of the way since the schedule doesn't know what's still alive
+	let mut game_state = level(1234);
+	game_state.spawners.iter_mut().for_each(|x| x.cap = 1000);
+	let expected = spawn_schedule(&game_state, 1500.0);
+	let mut spawned = Vec::new();
+	while game_state.time < 1500.0 {
+		let before = game_state.enemies.len();
+		game_state.tick(1.0);
+		for e in game_state.enemies[before..].iter() {
+			spawned.push(SpawnEvent { time: game_state.time, spawner: e.spawner.unwrap(), kind: e.kind, toughness: e.toughness });
+		}
+	}
+	assert!(expected.len() > 20);
+	assert_eq!(spawned, expected);
+}
+
+#[test]
+fn caps_are_per_spawner() { //the walker spawner being full mustn't stop the flyer one
+	let mut game_state = level(5);
+	while game_state.time < 250.0 {
+		game_state.tick(1.0);
+	}
+	let count = |i: usize| game_state.enemies.iter().filter(|x| x.spawner == Some(i)).count();
+	assert_eq!((count(0), count(1), count(2)), (2, 1, 1));
+	while game_state.time < 1000.0 {
+		game_state.tick(1.0);
+		let count = |i: usize| game_state.enemies.iter().filter(|x| x.spawner == Some(i)).count();
+		assert!(count(1) <= game_state.waves.cap(1, game_state.time));
+	}
+}
+
+#[test]
+fn negative_toughness_is_refused() {
+	let e = parse_level(&LEVEL.replace("waves 300 0.8 0.2", "waves 300 0.8 -0.2")).unwrap_err();
+	assert!(e.message.contains("toughness"), "{}", e);
+}
+
+#[test]
+fn no_waves_line_no_escalation() { //spawners go off exactly every interval, forever, and nothing toughens
+	let mut game_state = parse_level(&LEVEL.replace("waves 300 0.8 0.2", "")).unwrap();
+	game_state.seed(42);
+	let events = spawn_schedule(&game_state, 3000.0);
+	for (i, &interval) in [100.0, 150.0, 200.0].iter().enumerate() {
+		let times = events.iter().filter(|x| x.spawner == i).map(|x| x.time).collect::<Vec<_>>();
+		assert_eq!(times, (1..=(3000.0 / interval) as usize).map(|k| k as f64 * interval).collect::<Vec<_>>());
+	}
+	assert!(events.iter().all(|x| x.toughness == 1.0));
+	assert!((0..3000).all(|t| game_state.waves.cap(3, t as f64) == 3));
+}